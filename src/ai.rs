@@ -0,0 +1,42 @@
+use crate::snake::{Direction, Snake};
+use crate::types::Position;
+
+// Greedy steering for the title screen demo: take the safe move that ends closest to the food,
+// looking no further ahead than the next cell. `step` gives the cell a move would land on and
+// `is_hazard` whether landing there would crash.
+pub fn choose_direction(
+    snake: &Snake,
+    food: Position,
+    step: impl Fn(Direction) -> Position,
+    is_hazard: impl Fn(Position) -> bool,
+) -> Direction {
+    Direction::ALL
+        .into_iter()
+        .filter(|&direction| direction != snake.direction().opposite())
+        .min_by_key(|&direction| {
+            let next = step(direction);
+            (is_hazard(next), (next.x - food.x).abs() + (next.y - food.y).abs())
+        })
+        .unwrap_or(snake.direction())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GameConfig, WallMode};
+    
+    #[test]
+    fn heads_for_food_but_not_into_walls() {
+        let config = GameConfig::default();
+        let snake = Snake::new(Position::new(5, 10), 3, Direction::Right);
+        let step = |direction| snake.step(direction, &config, WallMode::Solid, &[]);
+        let head = snake.head();
+        
+        let food = Position::new(head.x, head.y - 5);
+        assert_eq!(choose_direction(&snake, food, step, |_| false), Direction::Up);
+        
+        let wall = Position::new(head.x, head.y - 1);
+        let direction = choose_direction(&snake, food, step, |cell| cell == wall);
+        assert!(direction != Direction::Up && direction != Direction::Left);
+    }
+}
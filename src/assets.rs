@@ -0,0 +1,56 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use macroquad::audio::load_sound;
+use macroquad::prelude::*;
+
+use crate::audio::Sounds;
+use crate::sprites::Sprites;
+
+const ASSETS_DIR: &str = "assets";
+const EAT_SOUND_FILE: &str = "assets/eat.wav";
+const DEATH_SOUND_FILE: &str = "assets/death.wav";
+const APPLE_TEXTURE_FILE: &str = "assets/apple.png";
+const FONT_FILE: &str = "assets/font.ttf";
+
+// Everything read from `assets/` at startup. Each one is optional, and whatever uses it falls back
+// to something plain when it's missing
+#[derive(Default)]
+pub struct Assets {
+    pub sounds: Sounds,
+    pub sprites: Sprites,
+    // `None` is macroquad's built-in font
+    pub font: Option<Font>,
+}
+
+// Tries every asset and reports the ones that fail on stderr, without giving up on the rest. The
+// window icon isn't here because it's needed before the window opens
+pub async fn load_assets() -> Assets {
+    if !Path::new(ASSETS_DIR).is_dir() {
+        eprintln!("{ASSETS_DIR}: no such directory, carrying on without sounds, sprites or font");
+        return Assets::default();
+    }
+    
+    let apple = loaded(APPLE_TEXTURE_FILE, load_texture(APPLE_TEXTURE_FILE).await);
+    if let Some(texture) = &apple {
+        texture.set_filter(FilterMode::Linear);
+    }
+    Assets {
+        sounds: Sounds {
+            eat: loaded(EAT_SOUND_FILE, load_sound(EAT_SOUND_FILE).await),
+            death: loaded(DEATH_SOUND_FILE, load_sound(DEATH_SOUND_FILE).await),
+        },
+        sprites: Sprites { apple },
+        font: loaded(FONT_FILE, load_ttf_font(FONT_FILE).await),
+    }
+}
+
+fn loaded<T, E: Display>(path: &str, result: Result<T, E>) -> Option<T> {
+    match result {
+        Ok(asset) => Some(asset),
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            None
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use macroquad::audio::{play_sound, stop_sound, PlaySoundParams, Sound};
+
+#[derive(Clone, Default)]
+pub struct Sounds {
+    pub eat: Option<Sound>,
+    pub death: Option<Sound>,
+}
+
+impl Sounds {
+    // `volume` runs from 0.0 to 1.0, and is 0.0 while muted
+    pub fn play(sound: &Option<Sound>, volume: f32) {
+        // Missing sounds were reported when loading, and are skipped here
+        if let Some(sound) = sound.as_ref().filter(|_| volume > 0.0) {
+            play_sound(
+                sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume,
+                },
+            );
+        }
+    }
+    
+    // Cut off anything still playing, so muting is heard straight away
+    pub fn stop_all(&self) {
+        for sound in [&self.eat, &self.death].into_iter().flatten() {
+            stop_sound(sound);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use macroquad::prelude::KeyCode;
+use std::fmt;
+
+use crate::snake::Direction;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Restart,
+    // Held rather than pressed: ticks come faster for as long as it's down
+    Boost,
+    // Cut off the end of the tail to get out of a tight spot
+    Sacrifice,
+}
+
+// Keys that can be saved in the settings file, looked up by their `KeyCode` name
+const BINDABLE_KEYS: [KeyCode; 46] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Tab, KeyCode::Backspace, KeyCode::LeftShift, KeyCode::RightShift,
+];
+
+// Keys `Game::handle_input` always reads for its own toggles, so they can never be bound to an action
+const RESERVED_KEYS: [KeyCode; 8] = [
+    KeyCode::G, KeyCode::L, KeyCode::B, KeyCode::F3, KeyCode::M, KeyCode::O, KeyCode::F5, KeyCode::Period,
+];
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Pause,
+        Action::Restart,
+        Action::Boost,
+        Action::Sacrifice,
+    ];
+    
+    pub fn steer(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Action::Up,
+            Direction::Down => Action::Down,
+            Direction::Left => Action::Left,
+            Direction::Right => Action::Right,
+        }
+    }
+    
+    fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::Boost => "boost",
+            Action::Sacrifice => "sacrifice",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BindingError {
+    // The key already triggers another action
+    KeyInUse { key: KeyCode, action: Action },
+    // The key is one of the `RESERVED_KEYS`
+    Reserved(KeyCode),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingError::KeyInUse { key, action } => write!(f, "{key:?} is already bound to {action:?}"),
+            BindingError::Reserved(key) => write!(f, "{key:?} is reserved for a display toggle"),
+        }
+    }
+}
+
+impl std::error::Error for BindingError {}
+
+// Which key triggers each player-one action; no key is ever bound to two actions
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    up: KeyCode,
+    down: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+    pause: KeyCode,
+    restart: KeyCode,
+    boost: KeyCode,
+    sacrifice: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            pause: KeyCode::P,
+            restart: KeyCode::Space,
+            boost: KeyCode::LeftShift,
+            sacrifice: KeyCode::X,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        match action {
+            Action::Up => self.up,
+            Action::Down => self.down,
+            Action::Left => self.left,
+            Action::Right => self.right,
+            Action::Pause => self.pause,
+            Action::Restart => self.restart,
+            Action::Boost => self.boost,
+            Action::Sacrifice => self.sacrifice,
+        }
+    }
+    
+    fn slot(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::Up => &mut self.up,
+            Action::Down => &mut self.down,
+            Action::Left => &mut self.left,
+            Action::Right => &mut self.right,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::Boost => &mut self.boost,
+            Action::Sacrifice => &mut self.sacrifice,
+        }
+    }
+    
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), BindingError> {
+        if RESERVED_KEYS.contains(&key) {
+            return Err(BindingError::Reserved(key));
+        }
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|&other| other != action && self.key(other) == key)
+        {
+            return Err(BindingError::KeyInUse { key, action: other });
+        }
+        *self.slot(action) = key;
+        Ok(())
+    }
+    
+    // One `action=key` line per binding, in the format of the settings file
+    pub fn to_config(&self) -> String {
+        Action::ALL
+            .map(|action| format!("{}={:?}\n", action.name(), self.key(action)))
+            .concat()
+    }
+    
+    // Apply one binding from the settings file; returns false, changing nothing, if the action or key is
+    // unknown, reserved or taken
+    pub fn apply_config(&mut self, action: &str, key: &str) -> bool {
+        let action = Action::ALL.into_iter().find(|candidate| candidate.name() == action);
+        let key = BINDABLE_KEYS.into_iter().find(|candidate| format!("{candidate:?}") == key);
+        match (action, key) {
+            (Some(action), Some(key)) => self.bind(action, key).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn keys_can_only_trigger_one_action() {
+        let mut bindings = KeyBindings::default();
+        
+        assert_eq!(
+            bindings.bind(Action::Up, KeyCode::P),
+            Err(BindingError::KeyInUse { key: KeyCode::P, action: Action::Pause })
+        );
+        assert_eq!(bindings.key(Action::Up), KeyCode::Up);
+        
+        assert_eq!(bindings.bind(Action::Up, KeyCode::I), Ok(()));
+        assert_eq!(bindings.bind(Action::Up, KeyCode::I), Ok(()));
+        assert_eq!(bindings.key(Action::Up), KeyCode::I);
+    }
+    
+    #[test]
+    fn reserved_keys_are_refused_from_code_and_the_settings_file() {
+        let mut bindings = KeyBindings::default();
+        for key in RESERVED_KEYS {
+            assert_eq!(bindings.bind(Action::Pause, key), Err(BindingError::Reserved(key)));
+        }
+        assert!(!bindings.apply_config("pause", "M"));
+        assert!(!bindings.apply_config("boost", "G"));
+        assert_eq!(bindings, KeyBindings::default());
+    }
+    
+    #[test]
+    fn bindings_survive_a_round_trip_through_the_config_format() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Pause, KeyCode::Key1).unwrap();
+        bindings.bind(Action::Up, KeyCode::Enter).unwrap();
+        
+        let mut loaded = KeyBindings::default();
+        for line in bindings.to_config().lines() {
+            let (action, key) = line.split_once('=').unwrap();
+            assert!(loaded.apply_config(action, key), "{line}");
+        }
+        assert_eq!(loaded, bindings);
+        assert!(!loaded.apply_config("jump", "J"));
+        assert!(!loaded.apply_config("up", "Escape"));
+    }
+}
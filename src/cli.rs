@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::settings::{Settings, Speed};
+use crate::types::WallMode;
+
+pub const USAGE: &str = "usage: snake_game_rust [LEVEL] [--speed slow|normal|fast] [--wrap] [--seed N] [--cells WxH]";
+
+// Boards outside this range either can't fit the starting snakes or have cells too small to see
+const MIN_CELLS: i32 = 12;
+const MAX_CELLS: i32 = 200;
+
+// What the game was launched with; any flag skips the title screen and starts playing straight away
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LaunchOptions {
+    pub level: Option<String>,
+    pub speed: Option<Speed>,
+    pub wrap: bool,
+    pub seed: Option<u64>,
+    // Board size in cells, overriding the board size setting
+    pub cells: Option<(i32, i32)>,
+    pub help: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownFlag(String),
+    MissingValue(&'static str),
+    InvalidValue { flag: &'static str, value: String },
+    // Only one level file can be given
+    UnexpectedArgument(String),
+    // The flags, together with the saved settings, make a combination the settings menu refuses
+    InvalidSettings,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownFlag(flag) => write!(f, "unknown flag '{flag}'"),
+            CliError::MissingValue(flag) => write!(f, "{flag} needs a value"),
+            CliError::InvalidValue { flag, value } => write!(f, "invalid value '{value}' for {flag}"),
+            CliError::UnexpectedArgument(argument) => write!(f, "unexpected argument '{argument}'"),
+            CliError::InvalidSettings => write!(f, "fast speed can't be played on the large board"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl LaunchOptions {
+    pub fn skips_menu(&self) -> bool {
+        self.speed.is_some() || self.wrap || self.seed.is_some() || self.cells.is_some()
+    }
+    
+    // `base` with the flags that map onto settings applied, checked the same way the settings menu is
+    pub fn settings(&self, base: Settings) -> Result<Settings, CliError> {
+        let mut settings = base;
+        if let Some(speed) = self.speed {
+            settings.speed = speed;
+        }
+        if self.wrap {
+            settings.wall_mode = WallMode::Wrap;
+        }
+        match settings.is_valid() {
+            true => Ok(settings),
+            false => Err(CliError::InvalidSettings),
+        }
+    }
+}
+
+// `args` excludes the program name
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<LaunchOptions, CliError> {
+    let mut options = LaunchOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--speed" => options.speed = Some(parse_value(&mut args, "--speed", parse_speed)?),
+            "--wrap" => options.wrap = true,
+            "--seed" => options.seed = Some(parse_value(&mut args, "--seed", |value| value.parse().ok())?),
+            "--cells" => options.cells = Some(parse_value(&mut args, "--cells", parse_cells)?),
+            "--help" | "-h" => options.help = true,
+            flag if flag.starts_with('-') => return Err(CliError::UnknownFlag(arg)),
+            _ if options.level.is_none() => options.level = Some(arg),
+            _ => return Err(CliError::UnexpectedArgument(arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_value<T>(
+    args: &mut impl Iterator<Item = String>,
+    flag: &'static str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<T, CliError> {
+    let value = args.next().ok_or(CliError::MissingValue(flag))?;
+    parse(&value).ok_or(CliError::InvalidValue { flag, value })
+}
+
+fn parse_speed(value: &str) -> Option<Speed> {
+    Speed::ALL.into_iter().find(|speed| speed.label().eq_ignore_ascii_case(value))
+}
+
+fn parse_cells(value: &str) -> Option<(i32, i32)> {
+    let (width, height) = value.split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    let in_range = |cells: i32| (MIN_CELLS..=MAX_CELLS).contains(&cells);
+    (in_range(width) && in_range(height)).then_some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::BoardSize;
+    
+    fn parse(args: &[&str]) -> Result<LaunchOptions, CliError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+    
+    #[test]
+    fn flags_and_a_level_are_parsed() {
+        let options = parse(&["--speed", "fast", "levels/cross.txt", "--wrap", "--seed", "123", "--cells", "40x30"]).unwrap();
+        
+        assert_eq!(options.level.as_deref(), Some("levels/cross.txt"));
+        assert_eq!(options.speed, Some(Speed::Fast));
+        assert!(options.wrap);
+        assert_eq!(options.seed, Some(123));
+        assert_eq!(options.cells, Some((40, 30)));
+        assert!(options.skips_menu());
+        assert!(!parse(&[]).unwrap().skips_menu());
+    }
+    
+    #[test]
+    fn bad_arguments_are_reported() {
+        assert_eq!(parse(&["--fly"]), Err(CliError::UnknownFlag("--fly".to_owned())));
+        assert_eq!(parse(&["--seed"]), Err(CliError::MissingValue("--seed")));
+        assert_eq!(
+            parse(&["--speed", "warp"]),
+            Err(CliError::InvalidValue { flag: "--speed", value: "warp".to_owned() })
+        );
+        assert!(matches!(parse(&["--cells", "40"]), Err(CliError::InvalidValue { .. })));
+        assert!(matches!(parse(&["--cells", "4x4"]), Err(CliError::InvalidValue { .. })));
+        assert_eq!(parse(&["a.txt", "b.txt"]), Err(CliError::UnexpectedArgument("b.txt".to_owned())));
+    }
+    
+    #[test]
+    fn flags_that_make_an_invalid_combination_are_refused() {
+        let large = Settings { board_size: BoardSize::Large, ..Settings::default() };
+        let options = parse(&["--speed", "fast"]).unwrap();
+        assert_eq!(options.settings(large), Err(CliError::InvalidSettings));
+        assert_eq!(options.settings(Settings::default()).map(|settings| settings.speed), Ok(Speed::Fast));
+    }
+}
@@ -0,0 +1,107 @@
+use macroquad::prelude::*;
+use std::cell::Cell;
+
+// A frame longer than this means the window was hidden, dragged or asleep rather than just slow, so
+// it only counts for this much
+const MAX_FRAME_GAP: f64 = 0.25;
+
+// Where the game reads the time from, so tests can drive it by hand instead of waiting on frames
+pub trait Clock {
+    // Seconds of play since the game started
+    fn now(&self) -> f64;
+    // Seconds the last frame took
+    fn frame_time(&self) -> f64;
+    // Called once at the start of every frame
+    fn start_frame(&self) {}
+}
+
+// The real clock, built up from macroquad's frame times so that time away from the window, which
+// arrives as one huge frame, reaches the game as no more than one slow frame. Pausing is left to the game, which moves its
+// timers past the pause when it resumes
+#[derive(Default)]
+pub struct GameClock {
+    now: Cell<f64>,
+    frame_time: Cell<f64>,
+}
+
+impl GameClock {
+    fn step(&self, frame_time: f64) {
+        let frame_time = frame_time.min(MAX_FRAME_GAP);
+        self.now.set(self.now.get() + frame_time);
+        self.frame_time.set(frame_time);
+    }
+}
+
+impl Clock for GameClock {
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+    
+    fn frame_time(&self) -> f64 {
+        self.frame_time.get()
+    }
+    
+    fn start_frame(&self) {
+        self.step(get_frame_time() as f64);
+    }
+}
+
+// A clock that only moves when told to; clones share the same time. Like `GameClock` it knows nothing
+// of pausing, so game tests go through the same timer shifting the real game does
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: std::rc::Rc<std::cell::Cell<f64>>,
+    frame_time: std::rc::Rc<std::cell::Cell<f64>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    // Finish a frame that took `seconds`
+    pub fn advance(&self, seconds: f64) {
+        self.now.set(self.now.get() + seconds);
+        self.frame_time.set(seconds);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+    
+    fn frame_time(&self) -> f64 {
+        self.frame_time.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn time_away_from_the_window_counts_as_one_slow_frame() {
+        let clock = GameClock::default();
+        for _ in 0..60 {
+            clock.step(1.0 / 60.0);
+        }
+        assert!((clock.now() - 1.0).abs() < 1e-9);
+        
+        // Minimised for half a minute
+        clock.step(30.0);
+        assert!((clock.now() - 1.0 - MAX_FRAME_GAP).abs() < 1e-9);
+        assert_eq!(clock.frame_time(), MAX_FRAME_GAP);
+        
+        clock.step(0.02);
+        assert!((clock.now() - 1.02 - MAX_FRAME_GAP).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn a_machine_under_four_frames_a_second_still_moves_the_clock() {
+        let clock = GameClock::default();
+        for _ in 0..4 {
+            clock.step(0.5);
+        }
+        assert!((clock.now() - 4.0 * MAX_FRAME_GAP).abs() < 1e-9);
+    }
+}
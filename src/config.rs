@@ -0,0 +1,69 @@
+use std::fs;
+
+use crate::bindings::KeyBindings;
+use crate::settings::Settings;
+
+const SETTINGS_FILE: &str = "settings.txt";
+// Binding lines are told apart from settings by this prefix, e.g. `key.pause=P`
+const BINDING_PREFIX: &str = "key.";
+
+// Settings and key bindings from the last session. Anything missing or unreadable in the file keeps
+// its default
+pub fn load_settings() -> (Settings, KeyBindings) {
+    fs::read_to_string(SETTINGS_FILE)
+        .map(|contents| parse_settings(&contents))
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &Settings, bindings: &KeyBindings) {
+    let bindings = bindings
+        .to_config()
+        .lines()
+        .map(|line| format!("{BINDING_PREFIX}{line}\n"))
+        .collect::<String>();
+    // Like the high score, losing the settings isn't worth interrupting the game over
+    let _ = fs::write(SETTINGS_FILE, settings.to_config() + &bindings);
+}
+
+fn parse_settings(contents: &str) -> (Settings, KeyBindings) {
+    let mut settings = Settings::default();
+    let mut bindings = KeyBindings::default();
+    for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+        let (key, value) = (key.trim(), value.trim());
+        match key.strip_prefix(BINDING_PREFIX) {
+            Some(action) => bindings.apply_config(action, value),
+            None => settings.apply_config(key, value),
+        };
+    }
+    
+    // A hand-edited file can pair settings the menu wouldn't allow; only the pair that clashes is reset
+    if !settings.is_valid() {
+        let defaults = Settings::default();
+        settings.speed = defaults.speed;
+        settings.board_size = defaults.board_size;
+    }
+    (settings, bindings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{BoardSize, Difficulty, Speed};
+    
+    #[test]
+    fn malformed_lines_fall_back_to_defaults() {
+        let (settings, bindings) = parse_settings("difficulty=Hard\ngarbage\nspeed=Ludicrous\nkey.up=Nope\n");
+        
+        assert_eq!(settings, Settings { difficulty: Difficulty::Hard, ..Settings::default() });
+        assert_eq!(bindings, KeyBindings::default());
+    }
+    
+    #[test]
+    fn an_invalid_combination_resets_only_the_settings_that_clash() {
+        let (settings, _) = parse_settings("difficulty=Hard\nspeed=Fast\nboard_size=Large\n");
+        
+        assert_eq!(settings, Settings { difficulty: Difficulty::Hard, ..Settings::default() });
+        assert_ne!(settings.speed, Speed::Fast);
+        assert_ne!(settings.board_size, BoardSize::Large);
+    }
+}
@@ -0,0 +1,425 @@
+use macroquad::prelude::*;
+use ::rand::prelude::*;
+use ::rand::rngs::StdRng;
+
+use crate::palette::Palette;
+use crate::types::{Bounds, Position, Viewport};
+
+const BONUS_FOOD_CHANCE: f64 = 0.15;
+const BONUS_FOOD_POINTS: u32 = 3;
+const BONUS_FOOD_LIFETIME: f64 = 5.0;
+const POISON_FOOD_CHANCE: f64 = 0.1;
+const POISON_FOOD_LIFETIME: f64 = 6.0;
+const FOCUS_FOOD_CHANCE: f64 = 0.05;
+const FOCUS_FOOD_LIFETIME: f64 = 5.0;
+const GOLDEN_FOOD_CHANCE: f64 = 0.03;
+const GOLDEN_FOOD_POINTS: u32 = 10;
+const GOLDEN_FOOD_LIFETIME: f64 = 3.0;
+const GOLDEN_PULSE_RATE: f64 = 8.0; // Radians per second
+const BIG_HEAD_FOOD_CHANCE: f64 = 0.05;
+const BIG_HEAD_FOOD_LIFETIME: f64 = 6.0;
+const FREEZE_FOOD_CHANCE: f64 = 0.03;
+const FREEZE_FOOD_LIFETIME: f64 = 5.0;
+// Extra food scattered by a golden food only stays this long
+const BURST_FOOD_LIFETIME: f64 = 6.0;
+// Share of a risk mode poison's side taken up by the mark that sets it apart
+const PAIR_MARK_SIZE: f32 = 0.3;
+// Runaway food hops to a neighbouring cell this often
+const MOVE_EVERY_TICKS: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FoodKind {
+    Normal,
+    Bonus,
+    // Shrinks the snake instead of growing it
+    Poison,
+    // Slows the game down for a few seconds
+    Focus,
+    // Rare and short-lived; worth a lot and scatters extra food when eaten
+    Golden,
+    // Makes the snake two cells wide for a while; only offered when big head food is switched on
+    BigHead,
+    // Stops runaway food and the timers that change the board for a while; only offered when
+    // something on the board moves or spawns by itself
+    Freeze,
+}
+
+impl FoodKind {
+    // One roll per pick either way, so switching big head or freeze food on doesn't shift the rest of
+    // the rng stream
+    fn random(rng: &mut StdRng, big_head: bool, freeze: bool) -> Self {
+        let roll: f64 = rng.gen();
+        if roll >= 1.0 - BIG_HEAD_FOOD_CHANCE {
+            match big_head {
+                true => FoodKind::BigHead,
+                false => FoodKind::Normal,
+            }
+        } else if freeze && roll >= 1.0 - BIG_HEAD_FOOD_CHANCE - FREEZE_FOOD_CHANCE {
+            FoodKind::Freeze
+        } else if roll < BONUS_FOOD_CHANCE {
+            FoodKind::Bonus
+        } else if roll < BONUS_FOOD_CHANCE + POISON_FOOD_CHANCE {
+            FoodKind::Poison
+        } else if roll < BONUS_FOOD_CHANCE + POISON_FOOD_CHANCE + FOCUS_FOOD_CHANCE {
+            FoodKind::Focus
+        } else if roll < BONUS_FOOD_CHANCE + POISON_FOOD_CHANCE + FOCUS_FOOD_CHANCE + GOLDEN_FOOD_CHANCE {
+            FoodKind::Golden
+        } else {
+            FoodKind::Normal
+        }
+    }
+    
+    pub fn points(self) -> u32 {
+        match self {
+            FoodKind::Normal | FoodKind::Focus | FoodKind::BigHead | FoodKind::Freeze => 1,
+            FoodKind::Bonus => BONUS_FOOD_POINTS,
+            FoodKind::Poison => 0,
+            FoodKind::Golden => GOLDEN_FOOD_POINTS,
+        }
+    }
+    
+    pub fn color(self, palette: &Palette) -> Color {
+        match self {
+            FoodKind::Normal => palette.food,
+            FoodKind::Bonus => palette.bonus_food,
+            FoodKind::Poison => palette.poison_food,
+            FoodKind::Focus => palette.focus_food,
+            FoodKind::Golden => palette.golden_food,
+            FoodKind::BigHead => palette.big_head_food,
+            FoodKind::Freeze => palette.freeze_food,
+        }
+    }
+    
+    // How long the food stays on the board before respawning elsewhere, if it expires at all
+    fn lifetime(self) -> Option<f64> {
+        match self {
+            FoodKind::Normal => None,
+            FoodKind::Bonus => Some(BONUS_FOOD_LIFETIME),
+            FoodKind::Poison => Some(POISON_FOOD_LIFETIME),
+            FoodKind::Focus => Some(FOCUS_FOOD_LIFETIME),
+            FoodKind::Golden => Some(GOLDEN_FOOD_LIFETIME),
+            FoodKind::BigHead => Some(BIG_HEAD_FOOD_LIFETIME),
+            FoodKind::Freeze => Some(FREEZE_FOOD_LIFETIME),
+        }
+    }
+}
+
+pub struct Food {
+    pub position: Position,
+    pub kind: FoodKind,
+    pub spawned_at: f64,
+    // When the food showed up on the board, for growing it in; unlike `spawned_at` this isn't moved
+    // on by the countdown or pauses, so food doesn't shrink back when play starts
+    appeared_at: f64,
+    // Ticks left before runaway food next moves
+    ticks_until_move: u32,
+    // Scattered by a golden food: removed once eaten or expired instead of respawning
+    pub temporary: bool,
+    // Whether respawning may pick big head food, or freeze food
+    pub big_head: bool,
+    pub freeze: bool,
+    // Part of a feast cluster: removed once eaten instead of respawning, and never expires
+    pub cluster: bool,
+    // One of a risk mode pair: drawn like normal food whatever it is, and never expires
+    pub pair: bool,
+    // Seeded so a run's food placement can be reproduced for replays
+    rng: StdRng,
+}
+
+impl Food {
+    // The food isn't placed on the board until the first call to `randomize`
+    pub fn new(now: f64, seed: u64) -> Self {
+        Self {
+            position: Position::new(0, 0),
+            kind: FoodKind::Normal,
+            spawned_at: now,
+            appeared_at: now,
+            ticks_until_move: MOVE_EVERY_TICKS,
+            temporary: false,
+            big_head: false,
+            freeze: false,
+            cluster: false,
+            pair: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    
+    // `is_free` reports whether a cell may hold food, e.g. it isn't covered by a snake or an obstacle.
+    // Returns false, leaving the food where it was, if no cell is free
+    pub fn randomize(&mut self, bounds: &Bounds, now: f64, is_free: impl Fn(Position) -> bool) -> bool {
+        self.kind = FoodKind::random(&mut self.rng, self.big_head, self.freeze);
+        self.spawned_at = now;
+        self.appeared_at = now;
+        self.place(&free_cells(bounds, is_free)).is_some()
+    }
+    
+    // A temporary normal food, seeded from this one so replays scatter it the same way; nothing if no
+    // cell is free
+    pub fn scatter(&mut self, bounds: &Bounds, now: f64, is_free: impl Fn(Position) -> bool) -> Option<Food> {
+        let mut food = Food::new(now, self.rng.gen());
+        food.temporary = true;
+        food.place(&free_cells(bounds, is_free))?;
+        Some(food)
+    }
+    
+    // `size` normal food on free cells that touch each other, grown outward from a random free cell;
+    // fewer if the cells around it run out, and none if no cell is free
+    pub fn cluster(&mut self, bounds: &Bounds, now: f64, size: usize, is_free: impl Fn(Position) -> bool) -> Vec<Food> {
+        let mut first = Food::new(now, self.rng.gen());
+        if first.place(&free_cells(bounds, &is_free)).is_none() {
+            return Vec::new();
+        }
+        let mut pieces = vec![first];
+        
+        while pieces.len() < size {
+            let taken: Vec<Position> = pieces.iter().map(|piece| piece.position).collect();
+            let open: Vec<Position> = taken
+                .iter()
+                .flat_map(|&cell| neighbours(cell))
+                .filter(|&cell| bounds.contains(cell) && is_free(cell) && !taken.contains(&cell))
+                .collect();
+            let Some(&cell) = open.choose(&mut self.rng) else {
+                break;
+            };
+            let mut piece = Food::new(now, self.rng.gen());
+            piece.position = cell;
+            pieces.push(piece);
+        }
+        for piece in &mut pieces {
+            piece.cluster = true;
+        }
+        pieces
+    }
+    
+    // A normal food and a poison one on two different free cells; the rng picks which comes first, so
+    // the poison isn't always the one placed earlier or nearer the top. Nothing if there aren't two
+    // free cells
+    pub fn pair(&mut self, bounds: &Bounds, now: f64, is_free: impl Fn(Position) -> bool) -> Option<[Food; 2]> {
+        let mut pieces = [Food::new(now, self.rng.gen()), Food::new(now, self.rng.gen())];
+        let poison = self.rng.gen_range(0..2);
+        pieces[poison].kind = FoodKind::Poison;
+        let mut free = free_cells(bounds, is_free);
+        let first = pieces[0].place(&free)?;
+        free.retain(|&cell| cell != first);
+        pieces[1].place(&free)?;
+        for piece in &mut pieces {
+            piece.pair = true;
+        }
+        Some(pieces)
+    }
+    
+    // Moves to a random cell of `free`; nothing happens if it's empty
+    fn place(&mut self, free: &[Position]) -> Option<Position> {
+        let &cell = free.choose(&mut self.rng)?;
+        self.position = cell;
+        Some(cell)
+    }
+    
+    // One tick of runaway food: every few ticks it hops to a random neighbouring cell inside `bounds`
+    // that `is_free` allows, staying put if it's boxed in
+    pub fn update(&mut self, bounds: &Bounds, is_free: impl Fn(Position) -> bool) {
+        self.ticks_until_move -= 1;
+        if self.ticks_until_move > 0 {
+            return;
+        }
+        self.ticks_until_move = MOVE_EVERY_TICKS;
+        
+        let open: Vec<Position> = neighbours(self.position)
+            .into_iter()
+            .filter(|&cell| bounds.contains(cell) && is_free(cell))
+            .collect();
+        if let Some(&cell) = open.choose(&mut self.rng) {
+            self.position = cell;
+        }
+    }
+    
+    pub fn is_expired(&self, now: f64) -> bool {
+        let lifetime = if self.temporary {
+            Some(BURST_FOOD_LIFETIME)
+        } else if self.pair {
+            None
+        } else {
+            self.kind.lifetime()
+        };
+        lifetime.is_some_and(|lifetime| now - self.spawned_at >= lifetime)
+    }
+    
+    // Risk mode pairs both take the normal colour, so the poison can't be spotted from across the board
+    pub fn color(&self, palette: &Palette) -> Color {
+        match self.pair {
+            true => palette.food,
+            false => self.kind.color(palette),
+        }
+    }
+    
+    // How far the food has grown in, from 0 when it appears to 1 after `grow_time` seconds; only for
+    // drawing, since it can be eaten the moment it appears
+    fn grown(&self, now: f64, grow_time: f64) -> f32 {
+        if grow_time <= 0.0 {
+            return 1.0;
+        }
+        let progress = ((now - self.appeared_at) / grow_time).clamp(0.0, 1.0) as f32;
+        // Eased out so it pops in quickly and settles at full size
+        1.0 - (1.0 - progress) * (1.0 - progress)
+    }
+    
+    // `padding` is the gap left around the food, in cells, and `grow_time` how long new food takes to
+    // grow to full size. Normal food, and both of a risk pair, are drawn as `apple` if it's given
+    pub fn draw(
+        &self,
+        viewport: &Viewport,
+        palette: &Palette,
+        apple: Option<&Texture2D>,
+        now: f64,
+        padding: f32,
+        grow_time: f64,
+    ) {
+        // Golden food pulses between three quarters and full size so it stands out
+        let pulse = match self.kind {
+            FoodKind::Golden => 0.125 * (1.0 - (now * GOLDEN_PULSE_RATE).sin()) as f32,
+            _ => 0.0,
+        };
+        let side = (1.0 - 2.0 * (padding + pulse)) * self.grown(now, grow_time);
+        let (x, y) = (self.position.x as f32, self.position.y as f32);
+        let inset = (1.0 - side) / 2.0;
+        match apple.filter(|_| self.kind == FoodKind::Normal || self.pair) {
+            Some(texture) => viewport.draw_inset_texture(x, y, 1.0, inset, texture),
+            None => viewport.draw_inset_cell(x, y, 1.0, inset, self.color(palette)),
+        }
+        // The poison of a pair gives itself away only with a small mark in the middle
+        if self.pair && self.kind == FoodKind::Poison {
+            viewport.draw_inset_cell(x, y, 1.0, 0.5 - side * PAIR_MARK_SIZE / 2.0, palette.poison_food);
+        }
+    }
+}
+
+fn neighbours(Position { x, y }: Position) -> [Position; 4] {
+    [
+        Position::new(x, y - 1),
+        Position::new(x, y + 1),
+        Position::new(x - 1, y),
+        Position::new(x + 1, y),
+    ]
+}
+
+// Cells inside `bounds` that `is_free` allows, row by row so a seeded pick among them is reproducible
+fn free_cells(bounds: &Bounds, is_free: impl Fn(Position) -> bool) -> Vec<Position> {
+    (bounds.top..bounds.bottom)
+        .flat_map(|y| (bounds.left..bounds.right).map(move |x| Position::new(x, y)))
+        .filter(|&cell| is_free(cell))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GameConfig;
+    
+    #[test]
+    fn same_seed_places_food_identically() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let is_free = |position: Position| position != Position::new(5, 10);
+        let mut first = Food::new(0.0, 42);
+        let mut second = Food::new(0.0, 42);
+        
+        for _ in 0..20 {
+            first.randomize(&bounds, 0.0, is_free);
+            second.randomize(&bounds, 0.0, is_free);
+            assert_eq!(first.position, second.position);
+            assert_eq!(first.kind, second.kind);
+        }
+    }
+    
+    #[test]
+    fn scattered_food_is_normal_and_short_lived() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut golden = Food::new(0.0, 9);
+        let scattered = golden.scatter(&bounds, 1.0, |_| true).unwrap();
+        
+        assert!(scattered.temporary);
+        assert_eq!(scattered.kind, FoodKind::Normal);
+        assert!(!scattered.is_expired(1.0 + BURST_FOOD_LIFETIME - 0.1));
+        assert!(scattered.is_expired(1.0 + BURST_FOOD_LIFETIME));
+    }
+    
+    #[test]
+    fn cluster_pieces_touch_and_avoid_taken_cells() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut source = Food::new(0.0, 3);
+        let blocked = Position::new(10, 10);
+        let pieces = source.cluster(&bounds, 0.0, 5, |position| position != blocked);
+        
+        assert_eq!(pieces.len(), 5);
+        let cells: Vec<Position> = pieces.iter().map(|piece| piece.position).collect();
+        for (index, piece) in pieces.iter().enumerate() {
+            assert!(piece.cluster && piece.kind == FoodKind::Normal);
+            assert_ne!(piece.position, blocked);
+            assert!(!cells[..index].contains(&piece.position));
+            if index > 0 {
+                assert!(neighbours(piece.position).iter().any(|cell| cells[..index].contains(cell)));
+            }
+        }
+    }
+    
+    #[test]
+    fn a_pair_is_one_normal_and_one_poison_on_free_cells() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut source = Food::new(0.0, 11);
+        let blocked = Position::new(10, 10);
+        
+        for _ in 0..20 {
+            let [first, second] = source.pair(&bounds, 0.0, |position| position != blocked).unwrap();
+            assert!(first.pair && second.pair);
+            assert_ne!(first.position, second.position);
+            assert!(first.position != blocked && second.position != blocked);
+            let poison = [first.kind, second.kind].iter().filter(|&&kind| kind == FoodKind::Poison).count();
+            assert_eq!(poison, 1);
+            assert!(!first.is_expired(100.0) && !second.is_expired(100.0));
+        }
+    }
+    
+    #[test]
+    fn a_full_board_spawns_nothing() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut food = Food::new(0.0, 13);
+        food.position = Position::new(4, 4);
+        
+        assert!(!food.randomize(&bounds, 0.0, |_| false));
+        assert_eq!(food.position, Position::new(4, 4));
+        assert!(food.scatter(&bounds, 0.0, |_| false).is_none());
+        assert!(food.cluster(&bounds, 0.0, 3, |_| false).is_empty());
+        // A pair needs two cells
+        let last = Position::new(7, 7);
+        assert!(food.pair(&bounds, 0.0, |position| position == last).is_none());
+        assert!(food.randomize(&bounds, 0.0, |position| position == last));
+        assert_eq!(food.position, last);
+    }
+    
+    #[test]
+    fn new_food_grows_in_without_moving() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut food = Food::new(0.0, 5);
+        food.randomize(&bounds, 2.0, |_| true);
+        
+        assert_eq!(food.grown(2.0, 0.2), 0.0);
+        assert!(food.grown(2.1, 0.2) > 0.5 && food.grown(2.1, 0.2) < 1.0);
+        assert_eq!(food.grown(2.2, 0.2), 1.0);
+        assert_eq!(food.grown(2.0, 0.0), 1.0);
+    }
+    
+    #[test]
+    fn runaway_food_only_steps_onto_free_neighbours() {
+        let bounds = Bounds::full(&GameConfig::default());
+        let mut food = Food::new(0.0, 7);
+        food.position = Position::new(0, 0);
+        // The only way out of the corner is down
+        let is_free = |position: Position| position != Position::new(1, 0);
+        
+        for _ in 0..MOVE_EVERY_TICKS - 1 {
+            food.update(&bounds, is_free);
+        }
+        assert_eq!(food.position, Position::new(0, 0));
+        food.update(&bounds, is_free);
+        assert_eq!(food.position, Position::new(0, 1));
+    }
+}
@@ -0,0 +1,2874 @@
+use macroquad::prelude::*;
+use ::rand::prelude::*;
+use ::rand::rngs::StdRng;
+use std::collections::{HashSet, VecDeque};
+
+use crate::ai::choose_direction;
+use crate::audio::Sounds;
+use crate::bindings::{Action, KeyBindings};
+use crate::cli::LaunchOptions;
+use crate::clock::{Clock, GameClock};
+use crate::config::save_settings;
+use crate::food::{Food, FoodKind};
+use crate::gamepad::GamepadInput;
+use crate::ghost::Ghost;
+use crate::highscore::{
+    daily_seed, format_date, load_high_score, load_leaderboard, now_timestamp, save_high_score, save_leaderboard,
+    Leaderboard,
+};
+use crate::level::{level_obstacles, portal_pairs, Level, START_LENGTH};
+use crate::palette::Palette;
+use crate::particles::Particle;
+use crate::popup::ScorePopup;
+use crate::replay::Replay;
+use crate::settings::{ControlScheme, GameMode, PlayerCount, Settings};
+use crate::snake::{Direction, SegmentStyle, Snake};
+use crate::sprites::Sprites;
+use crate::text;
+use crate::timestep::FixedTimestep;
+use crate::types::{Bounds, GameConfig, Position, Viewport, WallMode};
+
+const MIN_UPDATE_INTERVAL: f64 = 0.05;
+// Where player one's head starts on the built-in layouts; custom levels say for themselves. Either
+// moves to the nearest clear cell when something is in the way
+const PREFERRED_START: Position = Position { x: 5, y: 10 };
+const BOOST_FACTOR: f64 = 0.5; // Interval multiplier while boost is held
+// Modes with warp speed on go much faster for a few seconds every time the score passes another
+// multiple of `WARP_SCORE_STEP`, and may go past the usual top speed while they do
+const WARP_SCORE_STEP: u32 = 50;
+const WARP_SECONDS: f64 = 5.0;
+const WARP_FACTOR: f64 = 0.6;
+const WARP_MIN_INTERVAL: f64 = 0.03;
+const WARP_FLASH_RATE: f64 = 6.0;
+const SPEED_STEP: f64 = 0.97; // Interval multiplier per food eaten
+const POISON_SHRINK_SEGMENTS: usize = 2;
+const GOLDEN_BURST_SIZE: usize = 4; // Extra food scattered by a golden food
+const BIG_HEAD_SIZE: i32 = 2; // Width in cells of a snake that ate big head food
+const BIG_HEAD_TICKS: u32 = 40;
+const FREEZE_TICKS: u32 = 30;
+const REWIND_TICKS: usize = 7; // How far back a practice crash goes, about a second at normal speed
+const REWIND_FLASH_SECONDS: f64 = 0.3;
+const REWIND_FLASH_ALPHA: f32 = 0.5;
+const FEAST_CLUSTER_SIZE: usize = 5;
+// The stall limit shrinks by this factor for every segment grown, down to `STALL_MIN_SECONDS`
+const STALL_SHRINK: f64 = 0.98;
+const STALL_MIN_SECONDS: f64 = 5.0;
+const STALL_WARNING_SECONDS: f64 = 5.0; // The countdown shows once this little is left
+// Cutting off the tail costs this many segments and points, and can't be done again for a while
+const SACRIFICE_SEGMENTS: usize = 3;
+const SACRIFICE_PENALTY: u32 = 5;
+const SACRIFICE_COOLDOWN_SECONDS: f64 = 10.0;
+// Adaptive speed: eating `ADAPT_FAST_EATS` times within `ADAPT_WINDOW_SECONDS` nudges the game faster,
+// going `ADAPT_STARVE_SECONDS` without food nudges it slower, never by more than `ADAPT_STEP` a tick
+const ADAPT_WINDOW_SECONDS: f64 = 10.0;
+const ADAPT_FAST_EATS: usize = 3;
+const ADAPT_STARVE_SECONDS: f64 = 8.0;
+const ADAPT_FAST_SCALE: f64 = 0.85;
+const ADAPT_SLOW_SCALE: f64 = 1.2;
+const ADAPT_STEP: f64 = 0.002;
+const COMBO_WINDOW_TICKS: u64 = 20; // About three seconds at normal speed
+const SLOW_MOTION_SECONDS: f64 = 4.0;
+const SLOW_MOTION_FACTOR: f64 = 2.0; // Interval multiplier while slow motion lasts
+const COUNTDOWN_SECONDS: f64 = 3.0;
+const GO_DISPLAY_SECONDS: f64 = 0.5;
+const ARENA_SHRINK_SECONDS: f64 = 10.0;
+const ARENA_MIN_SIZE: i32 = 8; // The arena stops shrinking once either side is this many cells
+const WAVE_SECONDS: f64 = 8.0;
+const WAVE_MAX_COVERAGE: f64 = 0.2; // Waves stop once obstacles cover this share of the board
+const SHAKE_SECONDS: f64 = 0.3;
+const SHAKE_STRENGTH: f32 = 0.5; // Largest jitter right after the crash, in cells
+// After a crash the board darkens over this long before the game over text appears
+const DEATH_FADE_SECONDS: f64 = 0.6;
+const DEATH_FADE_ALPHA: f32 = 0.6;
+const DEMO_IDLE_SECONDS: f64 = 8.0; // Title screen idle time before the demo takes over
+const RESTART_CONFIRM_SECONDS: f64 = 2.0; // How long a first restart press waits for the second
+const MENU_ITEMS: [&str; 3] = ["Start", "Daily challenge", "Settings"];
+// Longer menus scroll to keep the selected row among this many
+const MENU_VISIBLE_ROWS: usize = 13;
+// Short enough that any typed seed fits in a u64
+const MAX_SEED_DIGITS: usize = 18;
+const SETTINGS_ROWS: usize = 26; // Difficulty, mode, walls, portals, speed, adaptive, food, runaway, big head, board size, palette, cell gap, food pop-in, apple sprite, players, hold to steer, controls, warning, goal, stall limit, wrong pick, warp speed, confirm restart, practice, volume, back
+const DEAD_SNAKE_ALPHA: f32 = 0.35;
+const GHOST_ALPHA: f32 = 0.25;
+const PATH_ALPHA: f32 = 0.3;
+const PATH_DOT_RADIUS: f32 = 0.12; // In cells
+// Player two's keys, which double as extra player-one keys in single player
+const SECOND_PLAYER_KEYS: [(KeyCode, Direction); 4] = [
+    (KeyCode::W, Direction::Up),
+    (KeyCode::S, Direction::Down),
+    (KeyCode::A, Direction::Left),
+    (KeyCode::D, Direction::Right),
+];
+const SPEED_BAR_WIDTH: f32 = 100.0;
+const SPEED_BAR_HEIGHT: f32 = 5.0;
+const GRID_LINE_THICKNESS: f32 = 1.0;
+const BORDER_THICKNESS: f32 = 4.0;
+const DANGER_BORDER_CELLS: f32 = 0.6; // Thickness of the warning border, in cells
+const DANGER_FLASH_RATE: f64 = 8.0;
+const MINIMAP_SIZE: f32 = 160.0; // Longest side of the minimap
+const MINIMAP_DOT_RADIUS: f32 = 1.5; // Smallest dot, so single cells stay visible
+const MINIMAP_ALPHA: f32 = 0.8;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameState {
+    Menu,
+    Settings,
+    Playing,
+    Paused,
+    GameOver,
+    Victory,
+}
+
+pub struct Game {
+    settings: Settings,
+    config: GameConfig,
+    palette: Palette,
+    // A board loaded from a file replaces the size and obstacle layout picked in settings
+    custom_level: Option<Level>,
+    custom_cells: Option<(i32, i32)>,
+    // Cells outside these are walled off; only shrinks in arena mode
+    bounds: Bounds,
+    last_shrink_at: f64,
+    // Survival waves: when the last one came, how many obstacles they've dropped this round, and
+    // where they drop them, seeded from the run so replays agree
+    last_wave_at: f64,
+    waves: usize,
+    wave_rng: StdRng,
+    sounds: Sounds,
+    sprites: Sprites,
+    snake: Snake,
+    // Player two's snake in two-player mode, steered with WASD
+    second_snake: Option<Snake>,
+    // `settings.food_count` items, each with its own seeded placement, or the current cluster in feast mode
+    foods: Vec<Food>,
+    // Where feast mode's clusters come from
+    cluster_source: Option<Food>,
+    // Where risk mode's pairs come from
+    pair_source: Option<Food>,
+    // Bursts from eaten food, updated every frame and dropped once faded
+    particles: Vec<Particle>,
+    popups: Vec<ScorePopup>,
+    obstacles: Vec<Position>,
+    portals: Vec<(Position, Position)>,
+    // Reaching this many segments wins the round; endless when unset
+    win_length: Option<usize>,
+    // Wall hits left that wrap the head around instead of ending the run, shared by both players
+    wraps_remaining: u32,
+    score: u32,
+    second_score: u32,
+    food_eaten: u32,
+    // Consecutive pickups each made within `COMBO_WINDOW_TICKS` of the last, each worth a point more.
+    // Counted in ticks rather than seconds so replays and boosted runs score the same
+    combo: u32,
+    last_eaten_tick: Option<u64>,
+    // When either snake last ate anything, poison included, for the stall limit
+    last_meal_at: f64,
+    // Player one can't cut off their tail again until this passes
+    sacrifice_ready_at: f64,
+    high_score: u32,
+    // Set when this round's score beat the high score it started with
+    beat_record: bool,
+    leaderboard: Leaderboard,
+    // Where this round's score landed on the leaderboard, if it made it
+    leaderboard_rank: Option<usize>,
+    state: GameState,
+    menu_selection: usize,
+    // The title screen plays a demo once it has been left alone for a while
+    menu_idle_since: f64,
+    demo: bool,
+    // Whether this round is the daily challenge, played on the day's seed
+    daily: bool,
+    // Digits typed on the title screen; a run started from there uses them as its seed
+    seed_entry: String,
+    paused_at: f64,
+    // When the snake first moved, and when the run ended, for the elapsed time readout
+    run_started_at: f64,
+    run_ended_at: f64,
+    // Drives the screen shake after a crash
+    died_at: Option<f64>,
+    // When restart was first pressed on the game over screen, while waiting for the second press
+    restart_requested_at: Option<f64>,
+    countdown_started_at: f64,
+    timestep: FixedTimestep,
+    update_interval: f64,
+    // Whether the boost key is held; ticks come at `tick_interval` rather than `update_interval`
+    boosting: bool,
+    // Set by focus food; the slowdown is folded into `update_interval` until this passes
+    slow_motion_until: Option<f64>,
+    // While set, warp speed is on; like slow motion it's folded into `update_interval`. The next burst
+    // comes once the score reaches `next_warp_score`
+    warp_until: Option<f64>,
+    next_warp_score: u32,
+    // Moves left before freeze food wears off, counted in ticks so replays thaw on the same one, and
+    // when it was eaten, so the arena and wave timers can skip the time spent frozen
+    freeze_ticks: u32,
+    frozen_at: f64,
+    // Both snakes as they were at the start of each of the last `REWIND_TICKS` ticks, oldest first,
+    // kept only in practice; and when a crash last sent them back, for the flash
+    snapshots: VecDeque<(Snake, Option<Snake>)>,
+    rewound_at: Option<f64>,
+    // While set, snakes can cross their own bodies; it wears off on a tick boundary so replays agree
+    tail_passthrough_until: Option<f64>,
+    // Time attack only: seconds left, drained every frame of play. Running out ends the round on the
+    // next tick boundary, which replays record
+    time_left: Option<f64>,
+    timed_out: bool,
+    // Multiplies the interval when adaptive speed is on, drifting with how often food is eaten
+    adaptive_scale: f64,
+    recent_eats: VecDeque<f64>,
+    tick_count: u64,
+    recording: Replay,
+    playback: Option<Replay>,
+    // Player one's path this run, and the path of the run that set the high score this session
+    ghost_recording: Ghost,
+    best_ghost: Option<Ghost>,
+    show_grid: bool,
+    show_length: bool,
+    // Dots from player one's head to the nearest food, to help new players find their way
+    show_path: bool,
+    // Frame rate, tick and head readouts in the corner, for chasing timing and collision bugs
+    debug_overlay: bool,
+    // Square until O is pressed, so the classic look is what a fresh game shows
+    segment_style: SegmentStyle,
+    // Debug builds only: while stepping, a tick runs per `step_requested` instead of on the timer
+    step_mode: bool,
+    step_requested: bool,
+    // Set once the player asks to leave; the main loop saves and exits when it sees it
+    quit_requested: bool,
+    bindings: KeyBindings,
+    clock: Box<dyn Clock>,
+}
+
+impl Game {
+    // `settings` already have the launch flags applied. Without a seed, food placement is seeded
+    // from entropy as usual; without a level the board comes from the settings
+    pub fn new(
+        options: &LaunchOptions,
+        settings: Settings,
+        bindings: KeyBindings,
+        sounds: Sounds,
+        sprites: Sprites,
+        level: Option<Level>,
+    ) -> Self {
+        let mut game = Self::with_clock(settings, sounds, options.seed, level, options.cells, Box::new(GameClock::default()));
+        game.bindings = bindings;
+        game.sprites = sprites;
+        if options.skips_menu() {
+            game.start();
+        }
+        game
+    }
+    
+    // Like `new`, but reading the time from `clock`; `cells` overrides the board size setting
+    fn with_clock(
+        settings: Settings,
+        sounds: Sounds,
+        seed: Option<u64>,
+        level: Option<Level>,
+        cells: Option<(i32, i32)>,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        let config = settings.board_size.config();
+        let now = clock.now();
+        
+        // The board and round state are filled in by `apply_settings` and `restart` below
+        let mut game = Self {
+            settings,
+            config,
+            palette: settings.palette.colors(),
+            custom_level: level,
+            custom_cells: cells,
+            bounds: Bounds::full(&config),
+            last_shrink_at: now,
+            last_wave_at: now,
+            waves: 0,
+            wave_rng: StdRng::seed_from_u64(0),
+            sounds,
+            sprites: Sprites::default(),
+            snake: Snake::new(Position::new(0, 0), 1, Direction::Right),
+            second_snake: None,
+            foods: Vec::new(),
+            cluster_source: None,
+            pair_source: None,
+            particles: Vec::new(),
+            popups: Vec::new(),
+            obstacles: Vec::new(),
+            portals: Vec::new(),
+            win_length: None,
+            wraps_remaining: 0,
+            score: 0,
+            second_score: 0,
+            food_eaten: 0,
+            combo: 0,
+            last_eaten_tick: None,
+            last_meal_at: now,
+            sacrifice_ready_at: now,
+            high_score: load_high_score(),
+            beat_record: false,
+            leaderboard: load_leaderboard(),
+            leaderboard_rank: None,
+            state: GameState::Menu,
+            menu_selection: 0,
+            daily: false,
+            menu_idle_since: now,
+            demo: false,
+            seed_entry: String::new(),
+            paused_at: 0.0,
+            run_started_at: now,
+            run_ended_at: now,
+            died_at: None,
+            restart_requested_at: None,
+            countdown_started_at: 0.0,
+            timestep: FixedTimestep::default(),
+            update_interval: settings.base_update_interval(),
+            boosting: false,
+            slow_motion_until: None,
+            warp_until: None,
+            next_warp_score: WARP_SCORE_STEP,
+            freeze_ticks: 0,
+            frozen_at: now,
+            snapshots: VecDeque::new(),
+            rewound_at: None,
+            tail_passthrough_until: None,
+            time_left: None,
+            timed_out: false,
+            adaptive_scale: 1.0,
+            recent_eats: VecDeque::new(),
+            tick_count: 0,
+            recording: Replay::default(),
+            playback: None,
+            ghost_recording: Ghost::default(),
+            best_ghost: None,
+            show_grid: false,
+            show_length: true,
+            show_path: false,
+            debug_overlay: false,
+            segment_style: SegmentStyle::Square,
+            step_mode: false,
+            step_requested: false,
+            quit_requested: false,
+            bindings: KeyBindings::default(),
+            clock,
+        };
+        game.apply_settings();
+        game.restart(seed);
+        game
+    }
+    
+    // Lay out the board for the current settings
+    fn apply_settings(&mut self) {
+        let mut config = self.settings.board_size.config();
+        if let Some((cell_number_x, cell_number_y)) = self.custom_cells {
+            config = config.with_cells(cell_number_x, cell_number_y);
+        }
+        self.palette = self.settings.palette.colors();
+        (self.config, self.obstacles) = match &self.custom_level {
+            Some(level) => (level.config(&config), level.obstacles.clone()),
+            None => (config, level_obstacles(self.settings.difficulty.params().level, &config)),
+        };
+        // A fresh layout has no wave obstacles on it for `restart` to clear
+        self.waves = 0;
+        
+        // Portals that would land on a custom level's walls are left out
+        self.portals = match self.settings.portals {
+            true => portal_pairs(&self.config),
+            false => Vec::new(),
+        };
+        self.portals
+            .retain(|(a, b)| !self.obstacles.contains(a) && !self.obstacles.contains(b));
+        
+        let cells = (self.config.cell_number_x * self.config.cell_number_y) as f64;
+        self.win_length = self.settings.win_target.board_fraction().map(|fraction| (cells * fraction) as usize);
+    }
+    
+    // Reset everything that belongs to a single round, keeping settings, the high score and view toggles
+    fn restart(&mut self, seed: Option<u64>) {
+        let seed = seed.unwrap_or_else(|| thread_rng().gen());
+        let now = self.clock.now();
+        
+        self.bounds = Bounds::full(&self.config);
+        // Clear the last round's wave obstacles off the end of the list
+        self.obstacles.truncate(self.obstacles.len() - self.waves);
+        self.waves = 0;
+        let (preferred, direction) = self
+            .custom_level
+            .as_ref()
+            .map_or((PREFERRED_START, Direction::Right), |level| (level.snake_start, level.snake_direction));
+        let two_players = self.settings.players == PlayerCount::Two;
+        // A board with no room anywhere for both snakes is left to player one. Only a board with no
+        // straight run of open floor at all is left with the preferred start
+        let (start, two_players) = match self.clear_start(preferred, direction, two_players) {
+            Some(start) => (start, two_players),
+            None => (self.clear_start(preferred, direction, false).unwrap_or(preferred), false),
+        };
+        self.wave_rng = StdRng::seed_from_u64(seed.rotate_left(32));
+        self.snake = Snake::new(start, START_LENGTH, direction);
+        // Player two starts mirrored across the middle row, so one heading up has the other heading down
+        self.second_snake = match two_players {
+            false => None,
+            true => {
+                let (mirrored, direction) = self.mirrored_start(start, direction);
+                Some(Snake::new(mirrored, START_LENGTH, direction))
+            }
+        };
+        self.particles.clear();
+        self.popups.clear();
+        self.wraps_remaining = self.settings.wall_mode.free_wraps();
+        self.score = 0;
+        self.second_score = 0;
+        self.food_eaten = 0;
+        self.combo = 0;
+        self.last_eaten_tick = None;
+        self.beat_record = false;
+        self.leaderboard_rank = None;
+        self.died_at = None;
+        self.restart_requested_at = None;
+        self.daily = false;
+        self.update_interval = self.settings.base_update_interval();
+        self.boosting = false;
+        self.slow_motion_until = None;
+        self.warp_until = None;
+        self.next_warp_score = WARP_SCORE_STEP;
+        self.freeze_ticks = 0;
+        self.snapshots.clear();
+        self.rewound_at = None;
+        self.tail_passthrough_until = None;
+        self.time_left = match self.settings.mode {
+            GameMode::TimeAttack => Some(self.settings.time_budget),
+            GameMode::Classic | GameMode::Arena | GameMode::Feast | GameMode::Risk | GameMode::Waves => None,
+        };
+        self.timed_out = false;
+        self.adaptive_scale = 1.0;
+        self.recent_eats.clear();
+        self.timestep.reset();
+        self.tick_count = 0;
+        self.recording = Replay::new(seed);
+        self.playback = None;
+        self.ghost_recording = Ghost::default();
+        self.foods.clear();
+        // Feast clusters all grow off one source seeded from the run, which never goes on the board itself
+        self.cluster_source = match self.settings.mode {
+            GameMode::Feast => Some(Food::new(now, seed)),
+            _ => None,
+        };
+        if self.cluster_source.is_some() {
+            self.spawn_cluster(now);
+            return;
+        }
+        // Risk pairs work the same way
+        self.pair_source = match self.settings.mode {
+            GameMode::Risk => Some(Food::new(now, seed)),
+            _ => None,
+        };
+        if self.pair_source.is_some() {
+            self.spawn_pair(now);
+            return;
+        }
+        // Each item gets its own stream off the run's seed so replays place them all the same way
+        // Respawning can drop an item on a full board, so each one goes at the end rather than at `stream`
+        for stream in 0..self.settings.food_count {
+            let index = self.foods.len();
+            self.foods.push(Food::new(now, seed.wrapping_add(stream as u64)));
+            self.foods[index].big_head = self.settings.big_head_food;
+            // Only worth offering when something on the board moves or changes by itself
+            self.foods[index].freeze =
+                self.settings.runaway_food || matches!(self.settings.mode, GameMode::Arena | GameMode::Waves);
+            match &self.custom_level {
+                Some(level) if stream == 0 => self.foods[0].position = level.food,
+                _ => self.respawn_food(index, now),
+            }
+        }
+    }
+    
+    // Player two's start: `head` mirrored across the middle row, heading the mirrored way
+    fn mirrored_start(&self, head: Position, direction: Direction) -> (Position, Direction) {
+        let mirrored = Position::new(head.x, self.config.cell_number_y - 1 - head.y);
+        let direction = match direction {
+            Direction::Up | Direction::Down => direction.opposite(),
+            Direction::Left | Direction::Right => direction,
+        };
+        (mirrored, direction)
+    }
+    
+    // Whether a snake started at `head` facing `direction`, and player two mirrored from it if asked
+    // for, would lie on open floor with the cell ahead of each head clear too, so no round can end on
+    // its first move
+    fn start_is_clear(&self, head: Position, direction: Direction, two_players: bool) -> bool {
+        let mut starts = vec![(head, direction)];
+        if two_players {
+            starts.push(self.mirrored_start(head, direction));
+        }
+        let mut taken = HashSet::new();
+        starts.into_iter().all(|(head, direction)| {
+            let (dx, dy) = direction.offset();
+            let ahead = Position::new(head.x + dx, head.y + dy);
+            Snake::start_cells(head, START_LENGTH, direction).chain([ahead]).all(|cell| {
+                self.bounds.contains(cell)
+                    && !self.obstacles.contains(&cell)
+                    && !self.portals.iter().any(|&(a, b)| cell == a || cell == b)
+                    && taken.insert(cell)
+            })
+        })
+    }
+    
+    // `preferred` if a snake can start there, otherwise the nearest cell that it can, if any
+    fn clear_start(&self, preferred: Position, direction: Direction, two_players: bool) -> Option<Position> {
+        let mut cells: Vec<Position> = (0..self.config.cell_number_y)
+            .flat_map(|y| (0..self.config.cell_number_x).map(move |x| Position::new(x, y)))
+            .collect();
+        cells.sort_by_key(|cell| (cell.x - preferred.x).abs() + (cell.y - preferred.y).abs());
+        cells.into_iter().find(|&cell| self.start_is_clear(cell, direction, two_players))
+    }
+    
+    pub fn update(&mut self) {
+        if self.state != GameState::Paused {
+            let dt = self.clock.frame_time() as f32;
+            self.particles.retain_mut(|particle| particle.update(dt));
+            let now = self.clock.now();
+            self.popups.retain(|popup| !popup.is_expired(now));
+        }
+        
+        match self.state {
+            GameState::Playing => self.update_playing(),
+            GameState::Menu => self.update_demo(),
+            GameState::Settings | GameState::Paused | GameState::GameOver | GameState::Victory => {}
+        }
+    }
+    
+    fn update_playing(&mut self) {
+        let current_time = self.clock.now();
+        if self.countdown_remaining(current_time) > 0.0 {
+            // Hold the snake in place; no time is banked until GO
+            return;
+        }
+        
+        if self.step_mode {
+            if std::mem::take(&mut self.step_requested) {
+                self.apply_tick_events(current_time);
+                self.tick(current_time);
+            }
+            return;
+        }
+        
+        if let Some(left) = &mut self.time_left {
+            *left = (*left - self.clock.frame_time()).max(0.0);
+        }
+        self.timestep.accumulate(self.clock.frame_time(), self.tick_interval());
+        while self.state == GameState::Playing && self.timestep.consume(self.tick_interval()) {
+            self.apply_tick_events(current_time);
+            self.tick(current_time);
+        }
+    }
+    
+    // The demo skips scoring, recording and game over: it just eats, and starts over when it crashes
+    fn update_demo(&mut self) {
+        let now = self.clock.now();
+        if !self.demo {
+            self.demo = now - self.menu_idle_since >= DEMO_IDLE_SECONDS;
+            return;
+        }
+        self.timestep.accumulate(self.clock.frame_time(), self.update_interval);
+        if !self.timestep.consume(self.update_interval) {
+            return;
+        }
+        
+        // Head for whichever food is closest
+        let head = self.snake.head();
+        let target = self
+            .foods
+            .iter()
+            .map(|food| food.position)
+            .min_by_key(|food| (food.x - head.x).abs() + (food.y - head.y).abs())
+            .unwrap_or(head);
+        let direction = choose_direction(
+            &self.snake,
+            target,
+            |direction| self.snake.step(direction, &self.config, self.settings.wall_mode, &self.portals),
+            |cell| self.is_hazard_for(&self.snake, cell),
+        );
+        self.snake.change_direction(direction);
+        self.snake.update(&self.config, self.settings.wall_mode, &self.portals);
+        
+        if self.has_crashed(&self.snake, self.second_snake.as_ref()) {
+            self.restart(None);
+        } else if let Some(index) = self.foods.iter().position(|food| food.position == self.snake.head()) {
+            self.snake.grow();
+            self.respawn_food(index, now);
+        }
+    }
+    
+    // Expiry and steering are resolved on tick boundaries so a replay can reproduce them exactly
+    fn apply_tick_events(&mut self, now: f64) {
+        // Slow motion only changes tick timing, never the ticks themselves, so it isn't recorded
+        if self.warp_until.is_some_and(|until| now >= until) {
+            self.warp_until = None;
+            self.recompute_speed();
+        }
+        if self.slow_motion_until.is_some_and(|until| now >= until) {
+            self.slow_motion_until = None;
+            self.recompute_speed();
+        }
+        
+        // Taken between ticks when played, so it goes before anything else on the tick
+        if self.playback.as_ref().is_some_and(|replay| replay.sacrifices_at(self.tick_count)) {
+            self.sacrifice_tail();
+        }
+        match &self.playback {
+            Some(replay) => {
+                for direction in replay.inputs_at(self.tick_count) {
+                    self.snake.change_direction(direction);
+                }
+                let expired: Vec<usize> = replay.food_expirations_at(self.tick_count).collect();
+                let arena_shrinks = replay.arena_shrinks_at(self.tick_count);
+                let wave = replay.waves_at(self.tick_count);
+                if replay.passthrough_ends_at(self.tick_count) {
+                    self.tail_passthrough_until = None;
+                }
+                self.timed_out = replay.times_out_at(self.tick_count);
+                for index in expired {
+                    self.respawn_food(index, now);
+                }
+                if arena_shrinks {
+                    self.shrink_arena(now);
+                }
+                if wave {
+                    self.spawn_wave();
+                }
+            }
+            None => {
+                // Starving under the stall limit ends the round the same way the time attack clock does
+                if self.time_left == Some(0.0) || self.stall_remaining(now).is_some_and(|left| left <= 0.0) {
+                    self.recording.record_time_out(self.tick_count);
+                    self.timed_out = true;
+                }
+                // Hold off while a head is inside a body, so the snake doesn't die the moment it turns solid
+                let head_in_body = self.snakes().any(|snake| snake.alive && snake.check_self_collision(&self.config));
+                if self.tail_passthrough_until.is_some_and(|until| now >= until) && !head_in_body {
+                    self.recording.record_passthrough_end(self.tick_count);
+                    self.tail_passthrough_until = None;
+                }
+                for index in (0..self.foods.len()).rev() {
+                    if self.foods[index].is_expired(now) {
+                        self.recording.record_food_expiration(self.tick_count, index);
+                        self.respawn_food(index, now);
+                    }
+                }
+                let frozen = self.freeze_ticks > 0;
+                if self.settings.mode == GameMode::Arena && !frozen && now - self.last_shrink_at >= ARENA_SHRINK_SECONDS {
+                    self.last_shrink_at += ARENA_SHRINK_SECONDS;
+                    if self.bounds.width() > ARENA_MIN_SIZE && self.bounds.height() > ARENA_MIN_SIZE {
+                        self.recording.record_arena_shrink(self.tick_count);
+                        self.shrink_arena(now);
+                    }
+                }
+                if self.settings.mode == GameMode::Waves && !frozen && now - self.last_wave_at >= WAVE_SECONDS {
+                    self.last_wave_at += WAVE_SECONDS;
+                    let cells = (self.config.cell_number_x * self.config.cell_number_y) as f64;
+                    if (self.obstacles.len() as f64) < cells * WAVE_MAX_COVERAGE {
+                        self.recording.record_wave(self.tick_count);
+                        self.spawn_wave();
+                    }
+                }
+            }
+        }
+    }
+    
+    // Snakes caught in the new wall are picked up by the collision check at the end of the tick
+    fn shrink_arena(&mut self, now: f64) {
+        self.bounds.shrink();
+        for index in (0..self.foods.len()).rev() {
+            if !self.bounds.contains(self.foods[index].position) {
+                self.respawn_food(index, now);
+            }
+        }
+    }
+    
+    // One obstacle on a random cell clear of the snakes, food, portals and other obstacles, and of
+    // the cells the heads move into next; nothing if the board has no such cell left. The pick comes
+    // from the round's seeded rng out of cells listed row by row, never in hash set order, so the
+    // same seed always lays out the same obstacles
+    fn spawn_wave(&mut self) {
+        let occupied = self.occupied_cells();
+        let free: Vec<Position> = (self.bounds.top..self.bounds.bottom)
+            .flat_map(|y| (self.bounds.left..self.bounds.right).map(move |x| Position::new(x, y)))
+            .filter(|cell| !occupied.contains(cell))
+            .collect();
+        if let Some(&cell) = free.choose(&mut self.wave_rng) {
+            self.obstacles.push(cell);
+            self.waves += 1;
+        }
+    }
+    
+    // Advance the simulation by exactly one step; `now` only timestamps events such as food spawns
+    fn tick(&mut self, now: f64) {
+        if self.timed_out {
+            self.end_round(GameState::GameOver, now);
+            return;
+        }
+        self.ghost_recording.record(self.snake.head(), self.snake.body.len());
+        if self.settings.practice {
+            if self.snapshots.len() == REWIND_TICKS {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back((self.snake.clone(), self.second_snake.clone()));
+        }
+        
+        // Borrow the snake fields directly so the rest of the board can be read alongside them
+        let snakes = std::iter::once(&mut self.snake).chain(self.second_snake.as_mut());
+        for snake in snakes.filter(|snake| snake.alive) {
+            snake.update(&self.config, self.settings.wall_mode, &self.portals);
+        }
+        // A freeze eaten this tick starts counting down from the next one
+        let frozen = self.freeze_ticks;
+        self.check_food_collision(now);
+        match frozen {
+            0 if self.settings.runaway_food => self.move_foods(),
+            0 => {}
+            _ => self.thaw(now),
+        }
+        self.adjust_difficulty(now);
+        self.check_game_over(now);
+        if self.state == GameState::Playing {
+            self.check_win(now);
+        }
+        self.tick_count += 1;
+    }
+    
+    // Drop the end of player one's tail at a cost in score; nothing happens if the snake is too short
+    // to spare it
+    fn sacrifice_tail(&mut self) -> bool {
+        if !self.snake.shrink(SACRIFICE_SEGMENTS) {
+            return false;
+        }
+        self.score = self.score.saturating_sub(SACRIFICE_PENALTY);
+        true
+    }
+    
+    fn steer(&mut self, direction: Direction) {
+        self.recording.record_input(self.tick_count, direction);
+        self.snake.change_direction(direction);
+    }
+    
+    fn snakes(&self) -> impl Iterator<Item = &Snake> {
+        std::iter::once(&self.snake).chain(self.second_snake.as_ref())
+    }
+    
+    // Both snakes can eat in the same tick, as long as it's different items. Newest items go first so
+    // removing a temporary one doesn't shift the ones still to be checked
+    fn check_food_collision(&mut self, now: f64) {
+        for index in (0..self.foods.len()).rev() {
+            self.check_food_eaten(index, now);
+        }
+    }
+    
+    fn check_food_eaten(&mut self, index: usize, now: f64) {
+        let food = self.foods[index].position;
+        let kind = self.foods[index].kind;
+        let points = kind.points();
+        
+        let config = &self.config;
+        let (snake, score) = if self.snake.alive && self.snake.head_covers(food, config) {
+            (&mut self.snake, &mut self.score)
+        } else if let Some(second) = self.second_snake.as_mut().filter(|snake| snake.alive && snake.head_covers(food, config)) {
+            (second, &mut self.second_score)
+        } else {
+            return;
+        };
+        self.particles.extend(Particle::burst(food, kind.color(&self.palette)));
+        self.last_meal_at = now;
+        
+        // Poison costs length instead of adding it, breaks the combo and doesn't count towards speeding up
+        if kind == FoodKind::Poison {
+            let fatal = self.foods[index].pair && self.settings.wrong_pick_ends_run;
+            if fatal || !snake.shrink(POISON_SHRINK_SEGMENTS) {
+                snake.alive = false;
+                Sounds::play(&self.sounds.death, self.settings.sound_volume());
+            }
+            self.combo = 0;
+            self.last_eaten_tick = None;
+            self.respawn_food(index, now);
+            return;
+        }
+        
+        match self.last_eaten_tick {
+            Some(last) if self.tick_count - last <= COMBO_WINDOW_TICKS => self.combo += 1,
+            _ => self.combo = 0,
+        }
+        self.last_eaten_tick = Some(self.tick_count);
+        self.recent_eats.push_back(now);
+        if let Some(left) = &mut self.time_left {
+            *left += self.settings.time_bonus;
+        }
+        
+        if kind == FoodKind::Focus {
+            self.slow_motion_until = Some(now + SLOW_MOTION_SECONDS);
+        }
+        // Counted in moves rather than seconds so replays shrink back on the same tick
+        if kind == FoodKind::BigHead {
+            snake.enlarge(BIG_HEAD_SIZE, BIG_HEAD_TICKS);
+        }
+        // A second one while frozen tops the freeze back up
+        if kind == FoodKind::Freeze {
+            if self.freeze_ticks == 0 {
+                self.frozen_at = now;
+            }
+            self.freeze_ticks = FREEZE_TICKS;
+        }
+        
+        snake.grow();
+        self.popups.push(ScorePopup::new(food, points + self.combo, self.combo, now));
+        *score += points + self.combo;
+        self.food_eaten += 1;
+        Sounds::play(&self.sounds.eat, self.settings.sound_volume());
+        if kind == FoodKind::Golden {
+            for _ in 0..GOLDEN_BURST_SIZE {
+                let occupied = self.occupied_cells();
+                let scattered = self.foods[index].scatter(&self.bounds, now, |position| !occupied.contains(&position));
+                self.foods.extend(scattered);
+            }
+        }
+        self.respawn_food(index, now);
+        self.check_warp(now);
+        self.recompute_speed();
+    }
+    
+    // One frozen tick gone; on the last, the arena and wave timers pick up where they were left
+    fn thaw(&mut self, now: f64) {
+        self.freeze_ticks -= 1;
+        if self.freeze_ticks == 0 {
+            let frozen_for = now - self.frozen_at;
+            self.last_shrink_at += frozen_for;
+            self.last_wave_at += frozen_for;
+        }
+    }
+    
+    // Warp only changes when ticks happen, so it's left out of replays the same way slow motion is
+    fn check_warp(&mut self, now: f64) {
+        if !self.settings.warp() || self.score < self.next_warp_score {
+            return;
+        }
+        while self.next_warp_score <= self.score {
+            self.next_warp_score += WARP_SCORE_STEP;
+        }
+        self.warp_until = Some(now + WARP_SECONDS);
+    }
+    
+    // The shortest interval ticks may come at right now
+    fn min_interval(&self) -> f64 {
+        match self.warp_until {
+            Some(_) => WARP_MIN_INTERVAL,
+            None => MIN_UPDATE_INTERVAL,
+        }
+    }
+    
+    // Cells food must not spawn on
+    fn occupied_cells(&self) -> HashSet<Position> {
+        self.snakes()
+            .flat_map(|snake| snake.body.iter().flat_map(|&segment| snake.covered_cells(segment, &self.config)))
+            .chain(self.obstacles.iter().copied())
+            .chain(self.portals.iter().flat_map(|&(a, b)| [a, b]))
+            .chain(self.foods.iter().map(|food| food.position))
+            // Keep food off the cells the heads are about to enter so it can't be eaten the moment it appears
+            .chain(
+                self.snakes()
+                    .filter(|snake| snake.alive)
+                    .map(|snake| snake.next_head(&self.config, self.settings.wall_mode, &self.portals)),
+            )
+            .collect()
+    }
+    
+    // Moves are decided by each item's seeded rng on tick boundaries, so replays follow them too
+    fn move_foods(&mut self) {
+        for index in 0..self.foods.len() {
+            let occupied = self.occupied_cells();
+            self.foods[index].update(&self.bounds, |position| !occupied.contains(&position));
+        }
+    }
+    
+    fn respawn_food(&mut self, index: usize, now: f64) {
+        if self.foods[index].temporary {
+            self.foods.remove(index);
+            return;
+        }
+        if self.foods[index].cluster {
+            self.foods.remove(index);
+            if self.foods.is_empty() {
+                self.spawn_cluster(now);
+            }
+            return;
+        }
+        // Whichever of a pair gets eaten, both make way for a new pair
+        if self.foods[index].pair {
+            self.foods.retain(|food| !food.pair);
+            self.spawn_pair(now);
+            return;
+        }
+        
+        // A board with no room left carries on with one food fewer
+        let occupied = self.occupied_cells();
+        if !self.foods[index].randomize(&self.bounds, now, |position| !occupied.contains(&position)) {
+            self.foods.remove(index);
+        }
+    }
+    
+    fn spawn_cluster(&mut self, now: f64) {
+        let occupied = self.occupied_cells();
+        if let Some(source) = &mut self.cluster_source {
+            let pieces = source.cluster(&self.bounds, now, FEAST_CLUSTER_SIZE, |position| !occupied.contains(&position));
+            self.foods.extend(pieces);
+        }
+    }
+    
+    fn spawn_pair(&mut self, now: f64) {
+        let occupied = self.occupied_cells();
+        if let Some(source) = &mut self.pair_source {
+            let pair = source.pair(&self.bounds, now, |position| !occupied.contains(&position));
+            self.foods.extend(pair.into_iter().flatten());
+        }
+    }
+    
+    // Boost only changes when ticks happen, never what they do, so like slow motion it isn't recorded
+    fn tick_interval(&self) -> f64 {
+        match self.boosting {
+            true => (self.update_interval * BOOST_FACTOR).max(self.min_interval()),
+            false => self.update_interval,
+        }
+    }
+    
+    fn set_boosting(&mut self, boosting: bool) {
+        let before = self.tick_interval();
+        self.boosting = boosting;
+        self.timestep.rescale(before, self.tick_interval());
+    }
+    
+    // Seconds left to eat something before the stall limit ends the round, if there is one. The
+    // longest snake sets the limit
+    fn stall_remaining(&self, now: f64) -> Option<f64> {
+        let base = self.settings.stall_limit? as f64;
+        let longest = self.snakes().map(|snake| snake.body.len()).max().unwrap_or(START_LENGTH);
+        let grown = longest.saturating_sub(START_LENGTH) as i32;
+        let limit = (base * STALL_SHRINK.powi(grown)).max(STALL_MIN_SECONDS);
+        Some(limit - (now - self.last_meal_at))
+    }
+    
+    fn recompute_speed(&mut self) {
+        let base_interval = self.settings.base_update_interval();
+        let scaled = base_interval * SPEED_STEP.powi(self.food_eaten as i32);
+        self.update_interval = scaled.clamp(MIN_UPDATE_INTERVAL, base_interval);
+        self.update_interval = (self.update_interval * self.adaptive_scale).max(MIN_UPDATE_INTERVAL);
+        if self.warp_until.is_some() {
+            self.update_interval = (self.update_interval * WARP_FACTOR).max(WARP_MIN_INTERVAL);
+        }
+        if self.slow_motion_until.is_some() {
+            self.update_interval *= SLOW_MOTION_FACTOR;
+        }
+    }
+    
+    fn adjust_difficulty(&mut self, now: f64) {
+        if !self.settings.adaptive_speed {
+            return;
+        }
+        
+        while self.recent_eats.front().is_some_and(|&eaten_at| now - eaten_at > ADAPT_WINDOW_SECONDS) {
+            self.recent_eats.pop_front();
+        }
+        // Time since the last meal, or since the run started if nothing has been eaten yet
+        let hungry_for = now - self.recent_eats.back().copied().unwrap_or(self.run_started_at);
+        
+        let target = if self.recent_eats.len() >= ADAPT_FAST_EATS {
+            ADAPT_FAST_SCALE
+        } else if hungry_for > ADAPT_STARVE_SECONDS {
+            ADAPT_SLOW_SCALE
+        } else {
+            1.0
+        };
+        self.adaptive_scale += (target - self.adaptive_scale).clamp(-ADAPT_STEP, ADAPT_STEP);
+        self.recompute_speed();
+    }
+    
+    fn check_game_over(&mut self, now: f64) {
+        self.use_free_wraps();
+        
+        let first_crashed = self.snake.alive && self.has_crashed(&self.snake, self.second_snake.as_ref());
+        let second_crashed = self
+            .second_snake
+            .as_ref()
+            .is_some_and(|second| second.alive && self.has_crashed(second, Some(&self.snake)));
+        
+        // Both are decided before either is marked dead so head-on collisions take out both snakes
+        if first_crashed {
+            self.snake.alive = false;
+        }
+        if let Some(second) = self.second_snake.as_mut().filter(|_| second_crashed) {
+            second.alive = false;
+        }
+        if first_crashed || second_crashed {
+            Sounds::play(&self.sounds.death, self.settings.sound_volume());
+        }
+        
+        // With nowhere left to go back to, a practice run ends like any other
+        if self.settings.practice && self.snakes().any(|snake| !snake.alive) && self.rewind(now) {
+            return;
+        }
+        
+        // In two-player mode the survivor keeps playing until they crash too
+        if self.snakes().all(|snake| !snake.alive) {
+            self.died_at = Some(now);
+            self.end_round(GameState::GameOver, now);
+        }
+    }
+    
+    // Put both snakes back where they were a moment ago, with any turns they had queued then dropped
+    // so the player gets to choose again. Snapshots the board has since closed in on, by an arena
+    // shrink or a wave, are passed over; returns false if none of them fit any more. It happens on a
+    // tick from the recorded turns alone, so a replay rewinds in the same places
+    fn rewind(&mut self, now: f64) -> bool {
+        while let Some((snake, second_snake)) = self.snapshots.pop_front() {
+            if !self.fits_board(&snake) || second_snake.as_ref().is_some_and(|second| !self.fits_board(second)) {
+                continue;
+            }
+            self.snake = snake;
+            self.second_snake = second_snake;
+            for snake in std::iter::once(&mut self.snake).chain(self.second_snake.as_mut()) {
+                snake.clear_turns();
+            }
+            self.rewound_at = Some(now);
+            return true;
+        }
+        false
+    }
+    
+    // Whether every cell `snake` covers is inside the current bounds and off the obstacles
+    fn fits_board(&self, snake: &Snake) -> bool {
+        snake.body.iter().all(|&segment| {
+            snake
+                .covered_cells(segment, &self.config)
+                .all(|cell| self.bounds.contains(cell) && !self.obstacles.contains(&cell))
+        })
+    }
+    
+    fn check_win(&mut self, now: f64) {
+        let Some(win_length) = self.win_length else {
+            return;
+        };
+        if self.snakes().any(|snake| snake.alive && snake.body.len() >= win_length) {
+            self.end_round(GameState::Victory, now);
+        }
+    }
+    
+    fn end_round(&mut self, state: GameState, now: f64) {
+        self.state = state;
+        self.run_ended_at = now;
+        self.combo = 0;
+        
+        // Practice runs don't count for anything, and a replay's score already counted when it was played
+        if self.settings.practice || self.playback.is_some() {
+            return;
+        }
+        let best = self.score.max(self.second_score);
+        // `high_score` only changes here, so it still holds the record from before this round
+        if best > self.high_score {
+            self.beat_record = true;
+            self.high_score = best;
+            save_high_score(self.high_score);
+            // Only a solo run can be raced against later
+            if self.second_snake.is_none() {
+                self.best_ghost = Some(std::mem::take(&mut self.ghost_recording));
+            }
+        }
+        
+        self.leaderboard_rank = self.leaderboard.insert(best, now_timestamp());
+        if self.leaderboard_rank.is_some() {
+            save_leaderboard(&self.leaderboard);
+        }
+    }
+    
+    fn use_free_wraps(&mut self) {
+        // Borrow the snake fields directly so the board config can be read alongside them
+        let snakes = std::iter::once(&mut self.snake).chain(self.second_snake.as_mut());
+        for snake in snakes.filter(|snake| snake.alive) {
+            if self.wraps_remaining > 0 && snake.check_wall_collision(&self.config, self.settings.wall_mode) {
+                snake.wrap_head(&self.config);
+                self.wraps_remaining -= 1;
+            }
+        }
+    }
+    
+    fn has_crashed(&self, snake: &Snake, other: Option<&Snake>) -> bool {
+        let config = &self.config;
+        let mut head = snake.covered_cells(snake.head(), config);
+        snake.check_wall_collision(config, self.settings.wall_mode)
+            || snake
+                .body
+                .iter()
+                .any(|&segment| snake.covered_cells(segment, config).any(|cell| !self.bounds.contains(cell)))
+            || (self.tail_passthrough_until.is_none() && snake.check_self_collision(config))
+            || head.any(|cell| self.obstacles.contains(&cell) || other.is_some_and(|other| other.covers(cell, config)))
+    }
+    
+    fn countdown_remaining(&self, now: f64) -> f64 {
+        (COUNTDOWN_SECONDS - (now - self.countdown_started_at)).max(0.0)
+    }
+    
+    // Seconds of actual play, not counting the countdown or time spent paused
+    fn elapsed(&self) -> f64 {
+        let now = match self.state {
+            GameState::Menu | GameState::Settings => return 0.0,
+            GameState::Playing => self.clock.now(),
+            GameState::Paused => self.paused_at,
+            GameState::GameOver | GameState::Victory => self.run_ended_at,
+        };
+        (now - self.run_started_at).max(0.0)
+    }
+    
+    fn start(&mut self) {
+        let now = self.clock.now();
+        self.state = GameState::Playing;
+        self.countdown_started_at = now;
+        self.timestep.reset();
+        for food in &mut self.foods {
+            food.spawned_at = now + COUNTDOWN_SECONDS;
+        }
+        self.last_shrink_at = now + COUNTDOWN_SECONDS;
+        self.last_wave_at = now + COUNTDOWN_SECONDS;
+        self.run_started_at = now + COUNTDOWN_SECONDS;
+        self.last_meal_at = self.run_started_at;
+        self.sacrifice_ready_at = self.run_started_at;
+        let passthrough = self.settings.difficulty.params().tail_passthrough_seconds;
+        if passthrough > 0.0 {
+            self.tail_passthrough_until = Some(self.run_started_at + passthrough);
+        }
+    }
+    
+    fn pause(&mut self) {
+        self.paused_at = self.clock.now();
+        self.state = GameState::Paused;
+    }
+    
+    fn resume(&mut self) {
+        // The clock keeps running through a pause, so shift the remaining timers forward past it
+        let paused_for = self.clock.now() - self.paused_at;
+        self.countdown_started_at += paused_for;
+        for food in &mut self.foods {
+            food.spawned_at += paused_for;
+        }
+        for popup in &mut self.popups {
+            popup.delay(paused_for);
+        }
+        self.last_shrink_at += paused_for;
+        self.last_wave_at += paused_for;
+        self.run_started_at += paused_for;
+        if let Some(until) = &mut self.slow_motion_until {
+            *until += paused_for;
+        }
+        if let Some(until) = &mut self.warp_until {
+            *until += paused_for;
+        }
+        self.frozen_at += paused_for;
+        if let Some(until) = &mut self.tail_passthrough_until {
+            *until += paused_for;
+        }
+        self.last_meal_at += paused_for;
+        self.sacrifice_ready_at += paused_for;
+        for eaten_at in &mut self.recent_eats {
+            *eaten_at += paused_for;
+        }
+        self.state = GameState::Playing;
+    }
+    
+    pub fn start_frame(&self) {
+        self.clock.start_frame();
+    }
+    
+    // The keys read directly here are `RESERVED_KEYS`, which player one's bindings can't take
+    pub fn handle_input(&mut self, gamepad: GamepadInput) {
+        if is_key_pressed(KeyCode::G) {
+            self.show_grid = !self.show_grid;
+        }
+        if is_key_pressed(KeyCode::L) {
+            self.show_length = !self.show_length;
+        }
+        if is_key_pressed(KeyCode::B) {
+            self.show_path = !self.show_path;
+        }
+        if is_key_pressed(KeyCode::F3) {
+            self.debug_overlay = !self.debug_overlay;
+        }
+        // Unlike the view toggles above this is a setting, so it's kept for next time
+        if is_key_pressed(KeyCode::M) {
+            self.settings.toggle_muted();
+            if self.settings.muted {
+                self.sounds.stop_all();
+            }
+            save_settings(&self.settings, &self.bindings);
+        }
+        if is_key_pressed(KeyCode::O) {
+            self.segment_style = match self.segment_style {
+                SegmentStyle::Rounded => SegmentStyle::Square,
+                SegmentStyle::Square => SegmentStyle::Rounded,
+            };
+        }
+        
+        // F5 freezes play so Period can advance it one tick at a time, for looking at collisions up close
+        if cfg!(debug_assertions) && self.state == GameState::Playing {
+            if is_key_pressed(KeyCode::F5) {
+                self.step_mode = !self.step_mode;
+            }
+            if is_key_pressed(KeyCode::Period) {
+                self.step_requested = true;
+            }
+        }
+        
+        // Play pauses first so a stray press doesn't throw a run away; the settings screen already
+        // takes Escape as back
+        if self.quit_pressed() {
+            match self.state {
+                GameState::Playing => self.pause(),
+                GameState::Settings => {}
+                GameState::Menu | GameState::Paused | GameState::GameOver | GameState::Victory => {
+                    self.quit_requested = true;
+                }
+            }
+        }
+        
+        match self.state {
+            GameState::Menu => self.handle_menu_input(gamepad),
+            GameState::Settings => self.handle_settings_input(gamepad),
+            GameState::Playing => self.handle_playing_input(gamepad),
+            GameState::Paused => self.handle_paused_input(),
+            GameState::GameOver | GameState::Victory => self.handle_game_over_input(gamepad),
+        }
+    }
+    
+    // Q only quits if it isn't bound to something else
+    fn quit_pressed(&self) -> bool {
+        let q_is_bound = Action::ALL.into_iter().any(|action| self.bindings.key(action) == KeyCode::Q);
+        is_key_pressed(KeyCode::Escape) || (is_key_pressed(KeyCode::Q) && !q_is_bound)
+    }
+    
+    pub fn should_quit(&self) -> bool {
+        self.quit_requested
+    }
+    
+    // Everything that's kept between runs. Each is already written as it changes, so this just makes
+    // sure the files match what's in memory before the game closes
+    pub fn save(&self) {
+        save_high_score(self.high_score);
+        save_leaderboard(&self.leaderboard);
+        save_settings(&self.settings, &self.bindings);
+    }
+    
+    fn pressed(&self, action: Action) -> bool {
+        is_key_pressed(self.bindings.key(action))
+    }
+    
+    // A single direction press for navigating menus
+    fn menu_direction(&self, gamepad: GamepadInput) -> Option<Direction> {
+        SECOND_PLAYER_KEYS
+            .into_iter()
+            .find(|&(letter, direction)| self.pressed(Action::steer(direction)) || is_key_pressed(letter))
+            .map(|(_, direction)| direction)
+            .or(gamepad.direction)
+    }
+    
+    fn move_selection(&mut self, direction: Option<Direction>, rows: usize) {
+        match direction {
+            Some(Direction::Up) => self.menu_selection = (self.menu_selection + rows - 1) % rows,
+            Some(Direction::Down) => self.menu_selection = (self.menu_selection + 1) % rows,
+            _ => {}
+        }
+    }
+    
+    fn handle_menu_input(&mut self, gamepad: GamepadInput) {
+        let direction = self.menu_direction(gamepad);
+        let confirmed = is_key_pressed(KeyCode::Space) || gamepad.start;
+        if direction.is_some() || confirmed {
+            self.menu_idle_since = self.clock.now();
+        }
+        
+        // Any steering or confirm during the demo jumps straight into a real game
+        if self.demo {
+            if direction.is_some() || confirmed {
+                self.demo = false;
+                self.restart(None);
+                self.start();
+            }
+            return;
+        }
+        
+        self.move_selection(direction, MENU_ITEMS.len());
+        self.edit_seed_entry();
+        if confirmed {
+            match self.menu_selection {
+                0 => {
+                    // Without a typed seed the round set up earlier, with its random seed, is kept
+                    if let Ok(seed) = self.seed_entry.parse() {
+                        self.restart(Some(seed));
+                    }
+                    self.start();
+                }
+                1 => {
+                    self.restart(Some(daily_seed(now_timestamp())));
+                    self.daily = true;
+                    self.start();
+                }
+                _ => {
+                    self.state = GameState::Settings;
+                    self.menu_selection = 0;
+                }
+            }
+        }
+    }
+    
+    fn edit_seed_entry(&mut self) {
+        let mut edited = false;
+        while let Some(typed) = get_char_pressed() {
+            if typed.is_ascii_digit() && self.seed_entry.len() < MAX_SEED_DIGITS {
+                self.seed_entry.push(typed);
+                edited = true;
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            edited |= self.seed_entry.pop().is_some();
+        }
+        if edited {
+            self.menu_idle_since = self.clock.now();
+        }
+    }
+    
+    fn handle_settings_input(&mut self, gamepad: GamepadInput) {
+        let direction = self.menu_direction(gamepad);
+        self.move_selection(direction, SETTINGS_ROWS);
+        
+        let forward = match direction {
+            Some(Direction::Left) => Some(false),
+            Some(Direction::Right) => Some(true),
+            _ => None,
+        };
+        if let Some(forward) = forward {
+            match self.menu_selection {
+                0 => self.settings.cycle_difficulty(forward),
+                1 => self.settings.cycle_mode(forward),
+                2 => self.settings.cycle_wall_mode(forward),
+                3 => self.settings.toggle_portals(),
+                4 => self.settings.cycle_speed(forward),
+                5 => self.settings.toggle_adaptive_speed(),
+                6 => self.settings.cycle_food_count(forward),
+                7 => self.settings.toggle_runaway_food(),
+                8 => self.settings.toggle_big_head_food(),
+                9 => self.settings.cycle_board_size(forward),
+                10 => self.settings.cycle_palette(forward),
+                11 => self.settings.cycle_segment_padding(forward),
+                12 => self.settings.cycle_food_grow_time(forward),
+                13 => self.settings.toggle_food_sprite(),
+                14 => self.settings.cycle_players(forward),
+                15 => self.settings.toggle_hold_to_steer(),
+                16 => self.settings.cycle_control_scheme(forward),
+                17 => self.settings.toggle_danger_warning(),
+                18 => self.settings.cycle_win_target(forward),
+                19 => self.settings.cycle_stall_limit(forward),
+                20 => self.settings.toggle_wrong_pick_ends_run(),
+                21 => self.settings.toggle_warp(),
+                22 => self.settings.toggle_confirm_restart(),
+                23 => self.settings.toggle_practice(),
+                24 => self.settings.cycle_volume(forward),
+                _ => {}
+            }
+            save_settings(&self.settings, &self.bindings);
+        }
+        
+        let confirmed = is_key_pressed(KeyCode::Space) || gamepad.start;
+        if is_key_pressed(KeyCode::Escape) || (confirmed && self.menu_selection == SETTINGS_ROWS - 1) {
+            // Lay the board out again so the menu backdrop matches the new settings
+            self.apply_settings();
+            self.restart(None);
+            self.state = GameState::Menu;
+            self.menu_selection = 2;
+            self.menu_idle_since = self.clock.now();
+        }
+    }
+    
+    fn handle_playing_input(&mut self, gamepad: GamepadInput) {
+        if self.pressed(Action::Pause) {
+            self.pause();
+            return;
+        }
+        // Replays are steered by their recorded inputs, and play at the speed they were recorded at so
+        // anything still timed in seconds comes out the same
+        if self.playback.is_some() {
+            return;
+        }
+        let boosting = is_key_down(self.bindings.key(Action::Boost));
+        if boosting != self.boosting {
+            self.set_boosting(boosting);
+        }
+        
+        let now = self.clock.now();
+        let counting_down = self.countdown_remaining(now) > 0.0;
+        if self.pressed(Action::Sacrifice) && !counting_down && now >= self.sacrifice_ready_at && self.sacrifice_tail() {
+            self.recording.record_sacrifice(self.tick_count);
+            self.sacrifice_ready_at = now + SACRIFICE_COOLDOWN_SECONDS;
+        }
+        
+        // The bound keys always steer player one; WASD steers player two when there is one
+        for (letter, direction) in SECOND_PLAYER_KEYS {
+            let bound = self.bindings.key(Action::steer(direction));
+            if self.pressed(Action::steer(direction)) || self.held(bound, &self.snake, direction) {
+                if let Some(turn) = self.key_direction(direction, &self.snake) {
+                    self.steer(turn);
+                }
+            }
+            let letter_steers = self.second_snake.as_ref().unwrap_or(&self.snake);
+            if is_key_pressed(letter) || self.held(letter, letter_steers, direction) {
+                let Some(turn) = self.key_direction(direction, letter_steers) else {
+                    continue;
+                };
+                match self.second_snake.as_mut() {
+                    Some(second) => second.change_direction(turn),
+                    None => self.steer(turn),
+                }
+            }
+        }
+        if let Some(direction) = gamepad.direction {
+            self.steer(direction);
+        }
+        if let Some(direction) = self.mouse_direction() {
+            self.steer(direction);
+        }
+    }
+    
+    // With hold to steer on, a held key turns `snake` whenever it isn't already heading that way. Keys
+    // that wouldn't turn it are skipped so holding one doesn't fill the turn buffer or the replay.
+    // Relative controls only turn on the press, or a held key would spin the snake round and round
+    fn held(&self, key: KeyCode, snake: &Snake, direction: Direction) -> bool {
+        let heading = snake.heading();
+        self.settings.hold_to_steer
+            && self.settings.control_scheme == ControlScheme::Absolute
+            && is_key_down(key)
+            && direction != heading
+            && direction != heading.opposite()
+    }
+    
+    // Where a direction key sends `snake` under the chosen control scheme. The gamepad and mouse always
+    // steer absolutely, since they point the way themselves
+    fn key_direction(&self, key: Direction, snake: &Snake) -> Option<Direction> {
+        match self.settings.control_scheme {
+            ControlScheme::Absolute => Some(key),
+            ControlScheme::Relative => match key {
+                Direction::Left => Some(snake.heading().rotated(false)),
+                Direction::Right => Some(snake.heading().rotated(true)),
+                Direction::Up | Direction::Down => None,
+            },
+        }
+    }
+    
+    // A click steers player one toward whichever side of its head was clicked
+    fn mouse_direction(&self) -> Option<Direction> {
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return None;
+        }
+        
+        let head = self.snake.head();
+        let (head_x, head_y) = self.board_viewport().to_screen(head.x as f32 + 0.5, head.y as f32 + 0.5);
+        let (mouse_x, mouse_y) = mouse_position();
+        let (dx, dy) = (mouse_x - head_x, mouse_y - head_y);
+        
+        let direction = match (dx.abs() > dy.abs(), dx > 0.0, dy > 0.0) {
+            (true, true, _) => Direction::Right,
+            (true, false, _) => Direction::Left,
+            (false, _, true) => Direction::Down,
+            (false, _, false) => Direction::Up,
+        };
+        Some(direction)
+    }
+    
+    fn handle_paused_input(&mut self) {
+        if self.pressed(Action::Pause) {
+            self.resume();
+        }
+    }
+    
+    fn handle_game_over_input(&mut self, gamepad: GamepadInput) {
+        if (self.pressed(Action::Restart) || gamepad.start) && self.confirm_restart() {
+            self.restart(None);
+            self.start();
+        }
+        
+        if is_key_pressed(KeyCode::R) {
+            self.start_replay();
+        }
+    }
+    
+    // Whether a restart press should go through. With confirmation on, the first press only arms it and
+    // the second has to follow within `RESTART_CONFIRM_SECONDS`
+    fn confirm_restart(&mut self) -> bool {
+        if !self.settings.confirm_restart || self.restart_pending() {
+            return true;
+        }
+        self.restart_requested_at = Some(self.clock.now());
+        false
+    }
+    
+    fn restart_pending(&self) -> bool {
+        self.restart_requested_at
+            .is_some_and(|at| self.clock.now() - at <= RESTART_CONFIRM_SECONDS)
+    }
+    
+    // Replays only record player one, so a round with a second snake can't be played back
+    fn can_replay(&self) -> bool {
+        self.second_snake.is_none()
+    }
+    
+    fn start_replay(&mut self) {
+        if !self.can_replay() {
+            return;
+        }
+        let replay = self.recording.clone();
+        let daily = self.daily;
+        self.restart(Some(replay.seed));
+        self.daily = daily;
+        self.recording = replay.clone();
+        self.playback = Some(replay);
+        self.start();
+    }
+    
+    fn draw_background(&self, viewport: &Viewport) {
+        clear_background(self.palette.background);
+        
+        // Draw grass pattern
+        let grass_color = self.palette.grass;
+        for row in 0..self.config.cell_number_y {
+            for col in 0..self.config.cell_number_x {
+                let should_draw = if row % 2 == 0 {
+                    col % 2 == 0
+                } else {
+                    col % 2 == 1
+                };
+                
+                if should_draw {
+                    viewport.draw_cell(Position::new(col, row), grass_color);
+                }
+            }
+        }
+        
+        if self.show_grid {
+            self.draw_grid_lines(viewport);
+        }
+        self.draw_border(viewport);
+    }
+    
+    // A solid frame marks edges that kill; in wrap mode it's dashed, one cell on and one off, since
+    // the snake comes back on the other side
+    fn draw_border(&self, viewport: &Viewport) {
+        let color = self.palette.border;
+        let (nx, ny) = (self.config.cell_number_x, self.config.cell_number_y);
+        let (left, top) = viewport.to_screen(0.0, 0.0);
+        let (right, bottom) = viewport.to_screen(nx as f32, ny as f32);
+        
+        if self.settings.wall_mode != WallMode::Wrap {
+            draw_rectangle(left, top, right - left, BORDER_THICKNESS, color);
+            draw_rectangle(left, bottom - BORDER_THICKNESS, right - left, BORDER_THICKNESS, color);
+            draw_rectangle(left, top, BORDER_THICKNESS, bottom - top, color);
+            draw_rectangle(right - BORDER_THICKNESS, top, BORDER_THICKNESS, bottom - top, color);
+            return;
+        }
+        
+        for col in (0..nx).step_by(2) {
+            let (x, _) = viewport.to_screen(col as f32, 0.0);
+            draw_rectangle(x, top, viewport.cell_width, BORDER_THICKNESS, color);
+            draw_rectangle(x, bottom - BORDER_THICKNESS, viewport.cell_width, BORDER_THICKNESS, color);
+        }
+        for row in (0..ny).step_by(2) {
+            let (_, y) = viewport.to_screen(0.0, row as f32);
+            draw_rectangle(left, y, BORDER_THICKNESS, viewport.cell_height, color);
+            draw_rectangle(right - BORDER_THICKNESS, y, BORDER_THICKNESS, viewport.cell_height, color);
+        }
+    }
+    
+    fn draw_grid_lines(&self, viewport: &Viewport) {
+        let (nx, ny) = (self.config.cell_number_x as f32, self.config.cell_number_y as f32);
+        for col in 0..=self.config.cell_number_x {
+            let (x, top) = viewport.to_screen(col as f32, 0.0);
+            let (_, bottom) = viewport.to_screen(col as f32, ny);
+            draw_line(x, top, x, bottom, GRID_LINE_THICKNESS, self.palette.grid);
+        }
+        for row in 0..=self.config.cell_number_y {
+            let (left, y) = viewport.to_screen(0.0, row as f32);
+            let (right, _) = viewport.to_screen(nx, row as f32);
+            draw_line(left, y, right, y, GRID_LINE_THICKNESS, self.palette.grid);
+        }
+    }
+    
+    // The board as drawn: all of it, or when it's too big for the window, a view that scrolls with
+    // player one's head. Gameplay never sees the difference
+    fn board_viewport(&self) -> Viewport {
+        let (x, y) = self.snake.draw_position(0, self.move_progress());
+        Viewport::current(&self.config).following(&self.config, vec2(x + 0.5, y + 0.5))
+    }
+    
+    pub fn draw(&self) {
+        // The board shakes but overlays and the HUD are drawn steady
+        let steady = Viewport::current(&self.config);
+        let board = self.board_viewport();
+        let viewport = board.clone().shifted(self.shake_offset(&board));
+        self.draw_background(&viewport);
+        self.draw_arena_walls(&viewport);
+        let padding = self.settings.segment_padding;
+        for obstacle in &self.obstacles {
+            viewport.draw_inset_cell(obstacle.x as f32, obstacle.y as f32, 1.0, padding, self.palette.obstacle);
+        }
+        for &(a, b) in &self.portals {
+            viewport.draw_cell(a, self.palette.portal);
+            viewport.draw_cell(b, self.palette.portal);
+        }
+        let apple = self.sprites.apple.as_ref().filter(|_| self.settings.food_sprite);
+        for food in &self.foods {
+            food.draw(&viewport, &self.palette, apple, self.clock.now(), padding, self.settings.food_grow_time);
+        }
+        self.draw_ghost(&viewport);
+        self.draw_path(&viewport);
+        let progress = self.move_progress();
+        self.draw_snake(&self.snake, &viewport, progress, self.palette.snake, self.palette.snake_head);
+        if let Some(second) = &self.second_snake {
+            self.draw_snake(second, &viewport, progress, self.palette.second_snake, self.palette.second_snake_head);
+        }
+        for particle in &self.particles {
+            particle.draw(&viewport);
+        }
+        for popup in &self.popups {
+            popup.draw(&viewport, self.clock.now(), self.palette.text);
+        }
+        
+        let viewport = steady;
+        if viewport.needs_scrolling() {
+            // Rows scrolled off the top would otherwise show through the HUD strip
+            draw_rectangle(0.0, 0.0, viewport.width, viewport.hud_height, self.palette.background);
+            self.draw_minimap(&viewport, &board);
+        }
+        let death_fade = self.death_fade();
+        if let Some(fade) = death_fade {
+            draw_rectangle(0.0, 0.0, viewport.width, viewport.height, Color::new(0.0, 0.0, 0.0, fade * DEATH_FADE_ALPHA));
+        }
+        if self.slow_motion_until.is_some() {
+            draw_rectangle(0.0, 0.0, viewport.width, viewport.height, self.palette.slow_motion_tint);
+        }
+        if self.freeze_ticks > 0 {
+            draw_rectangle(0.0, 0.0, viewport.width, viewport.height, self.palette.freeze_tint);
+        }
+        if let Some(rewound_at) = self.rewound_at {
+            let fade = 1.0 - ((self.clock.now() - rewound_at) / REWIND_FLASH_SECONDS) as f32;
+            if fade > 0.0 {
+                draw_rectangle(0.0, 0.0, viewport.width, viewport.height, Color::new(1.0, 1.0, 1.0, fade * REWIND_FLASH_ALPHA));
+            }
+        }
+        if self.settings.danger_warning && self.state == GameState::Playing && self.danger_ahead() {
+            self.draw_danger_border(&viewport);
+        }
+        self.draw_hud(&viewport);
+        
+        match self.state {
+            GameState::Menu => self.draw_menu(&viewport),
+            GameState::Settings => self.draw_settings(&viewport),
+            GameState::Playing => self.draw_countdown(&viewport),
+            GameState::Paused => self.draw_paused(&viewport),
+            // Restarting works straight away; only the text waits for the fade
+            GameState::GameOver | GameState::Victory => {
+                if death_fade.is_none_or(|fade| fade >= 1.0) {
+                    self.draw_game_over(&viewport);
+                }
+            }
+        }
+    }
+    
+    // The whole board shrunk into the bottom-right corner as dots, with the part in view outlined
+    fn draw_minimap(&self, screen: &Viewport, board: &Viewport) {
+        let (nx, ny) = (self.config.cell_number_x as f32, self.config.cell_number_y as f32);
+        let scale = MINIMAP_SIZE * screen.text_scale / nx.max(ny);
+        let margin = 10.0 * screen.text_scale;
+        let (left, top) = (screen.width - nx * scale - margin, screen.height - ny * scale - margin);
+        draw_rectangle(left, top, nx * scale, ny * scale, Color { a: MINIMAP_ALPHA, ..self.palette.background });
+        draw_rectangle_lines(left, top, nx * scale, ny * scale, 1.0, self.palette.text);
+        
+        let radius = (scale / 2.0).max(MINIMAP_DOT_RADIUS);
+        let dot = |cell: Position, color: Color| {
+            draw_circle(left + (cell.x as f32 + 0.5) * scale, top + (cell.y as f32 + 0.5) * scale, radius, color);
+        };
+        for &obstacle in &self.obstacles {
+            dot(obstacle, self.palette.obstacle);
+        }
+        for food in &self.foods {
+            dot(food.position, food.color(&self.palette));
+        }
+        for &segment in &self.snake.body {
+            dot(segment, self.palette.snake);
+        }
+        for &segment in self.second_snake.iter().flat_map(|second| &second.body) {
+            dot(segment, self.palette.second_snake);
+        }
+        
+        let view_x = -board.offset.x / board.cell_width;
+        let view_y = (board.hud_height - board.offset.y) / board.cell_height;
+        let view_width = board.width / board.cell_width;
+        let view_height = (board.height - board.hud_height) / board.cell_height;
+        draw_rectangle_lines(
+            left + view_x * scale,
+            top + view_y * scale,
+            view_width * scale,
+            view_height * scale,
+            1.0,
+            self.palette.highlight,
+        );
+    }
+    
+    // How far the board has darkened since a crash ended the round, from 0.0 to 1.0
+    fn death_fade(&self) -> Option<f32> {
+        let died_at = self.died_at.filter(|_| self.state == GameState::GameOver)?;
+        Some(((self.clock.now() - died_at) / DEATH_FADE_SECONDS).min(1.0) as f32)
+    }
+    
+    // Purely a hint for the player: whether player one's next move, as things stand, would crash
+    fn danger_ahead(&self) -> bool {
+        if !self.snake.alive || self.countdown_remaining(self.clock.now()) > 0.0 {
+            return false;
+        }
+        
+        let next = self.snake.next_head(&self.config, self.settings.wall_mode, &self.portals);
+        self.is_hazard_for(&self.snake, next)
+    }
+    
+    // Whether `snake` would crash by moving its head onto `cell` next tick
+    fn is_hazard_for(&self, snake: &Snake, cell: Position) -> bool {
+        // The tail moves out of the way unless the snake is about to grow; none of the body is in the
+        // way while tail passthrough lasts
+        let tail_len = match self.tail_passthrough_until {
+            Some(_) => 0,
+            None => snake.body.len() - usize::from(!snake.is_growing()),
+        };
+        
+        // Bounds never extend past the board, so this covers solid walls too
+        !self.bounds.contains(cell)
+            || self.obstacles.contains(&cell)
+            || snake.body.iter().take(tail_len).any(|&segment| segment == cell)
+            || self
+                .snakes()
+                .any(|other| !std::ptr::eq(other, snake) && other.body.contains(&cell))
+    }
+    
+    fn draw_danger_border(&self, viewport: &Viewport) {
+        // Pulse between half and full strength
+        let pulse = 0.75 + 0.25 * (self.clock.now() * DANGER_FLASH_RATE).sin() as f32;
+        let color = Color { a: self.palette.danger.a * pulse, ..self.palette.danger };
+        let thickness = DANGER_BORDER_CELLS * viewport.cell_width.min(viewport.cell_height);
+        // Frame the board, not the HUD strip above it
+        let top = viewport.hud_height;
+        let side_height = viewport.height - top - 2.0 * thickness;
+        
+        draw_rectangle(0.0, top, viewport.width, thickness, color);
+        draw_rectangle(0.0, viewport.height - thickness, viewport.width, thickness, color);
+        draw_rectangle(0.0, top + thickness, thickness, side_height, color);
+        draw_rectangle(viewport.width - thickness, top + thickness, thickness, side_height, color);
+    }
+    
+    // Random jitter that fades out over `SHAKE_SECONDS` after a crash
+    fn shake_offset(&self, viewport: &Viewport) -> Vec2 {
+        let Some(died_at) = self.died_at else {
+            return Vec2::ZERO;
+        };
+        let remaining = 1.0 - ((self.clock.now() - died_at) / SHAKE_SECONDS) as f32;
+        if remaining <= 0.0 {
+            return Vec2::ZERO;
+        }
+        
+        let mut rng = thread_rng();
+        let amplitude = SHAKE_STRENGTH * remaining;
+        vec2(
+            rng.gen_range(-amplitude..=amplitude) * viewport.cell_width,
+            rng.gen_range(-amplitude..=amplitude) * viewport.cell_height,
+        )
+    }
+    
+    fn draw_arena_walls(&self, viewport: &Viewport) {
+        for row in 0..self.config.cell_number_y {
+            for col in 0..self.config.cell_number_x {
+                let position = Position::new(col, row);
+                if !self.bounds.contains(position) {
+                    viewport.draw_cell(position, self.palette.arena_wall);
+                }
+            }
+        }
+    }
+    
+    // A snake that crashed while the other player carries on is drawn faded out
+    fn draw_snake(&self, snake: &Snake, viewport: &Viewport, progress: f32, body: Color, head: Color) {
+        let fade = |color: Color| match snake.alive || matches!(self.state, GameState::GameOver | GameState::Victory) {
+            true => color,
+            false => Color { a: DEAD_SNAKE_ALPHA, ..color },
+        };
+        snake.draw(viewport, progress, fade(body), fade(head), self.segment_style, self.settings.segment_padding);
+    }
+    
+    // The high-score run, moving in step with this one; it's only for show and nothing collides with it
+    fn draw_ghost(&self, viewport: &Viewport) {
+        let Some(ghost) = &self.best_ghost else {
+            return;
+        };
+        if self.playback.is_some() || !matches!(self.state, GameState::Playing | GameState::Paused) {
+            return;
+        }
+        
+        let color = Color { a: GHOST_ALPHA, ..self.palette.snake };
+        for cell in ghost.body_at(self.tick_count) {
+            viewport.draw_cell(cell, color);
+        }
+    }
+    
+    // Only a hint: the dots go straight through walls, obstacles and the snake itself. Poison is never
+    // pointed at, and neither is a risk mode pair, since that would give away which one is safe
+    fn draw_path(&self, viewport: &Viewport) {
+        if !self.show_path || !matches!(self.state, GameState::Playing | GameState::Paused) {
+            return;
+        }
+        let head = self.snake.head();
+        let distance = |cell: Position| (cell.x - head.x).abs() + (cell.y - head.y).abs();
+        let Some(food) = self
+            .foods
+            .iter()
+            .filter(|food| food.kind != FoodKind::Poison && !food.pair)
+            .map(|food| food.position)
+            .min_by_key(|&cell| distance(cell))
+        else {
+            return;
+        };
+        
+        let color = Color { a: PATH_ALPHA, ..self.palette.text };
+        let radius = PATH_DOT_RADIUS * viewport.cell_width.min(viewport.cell_height);
+        for cell in breadcrumb_path(head, food) {
+            let (x, y) = viewport.to_screen(cell.x as f32 + 0.5, cell.y as f32 + 0.5);
+            draw_circle(x, y, radius, color);
+        }
+    }
+    
+    // Fraction of the current tick that has elapsed; nothing builds up while paused, so it holds still
+    fn move_progress(&self) -> f32 {
+        match self.state {
+            GameState::GameOver | GameState::Victory => 1.0,
+            _ => self.timestep.progress(self.tick_interval()) as f32,
+        }
+    }
+    
+    fn draw_hud(&self, viewport: &Viewport) {
+        let text_scale = viewport.text_scale;
+        // Baselines for the strip above the board, and for notices drawn just inside the board's top edge
+        let strip_y = viewport.hud_height - 10.0 * text_scale;
+        let notice_y = viewport.hud_height + 30.0 * text_scale;
+        draw_line(0.0, viewport.hud_height, viewport.width, viewport.hud_height, 2.0, self.palette.grid);
+        
+        // Draw score and high score
+        let score_text = format!("{}", self.score);
+        text::draw(
+            &score_text,
+            viewport.width - 60.0 * text_scale,
+            strip_y,
+            30.0 * text_scale,
+            self.palette.text,
+        );
+        let high_score_text = format!("HI {}", self.high_score);
+        text::draw(
+            &high_score_text,
+            viewport.width - 170.0 * text_scale,
+            strip_y,
+            30.0 * text_scale,
+            self.palette.text,
+        );
+        
+        // Length and score drift apart with bonus and poison food, so length gets its own readout
+        if self.show_length {
+            let length_text = format!("LEN {}", self.snake.body.len());
+            text::draw(
+                &length_text,
+                viewport.width - 280.0 * text_scale,
+                strip_y,
+                30.0 * text_scale,
+                self.palette.text,
+            );
+        }
+        
+        if self.combo > 0 {
+            let combo_text = format!("COMBO x{}", self.combo + 1);
+            let combo_width = text::measure(&combo_text, 24.0 * text_scale).width;
+            text::draw(
+                &combo_text,
+                viewport.width - combo_width - 10.0 * text_scale,
+                viewport.height - 20.0 * text_scale,
+                24.0 * text_scale,
+                self.palette.highlight,
+            );
+        }
+        
+        // Player two's score follows the run stats on the left
+        if self.second_snake.is_some() {
+            text::draw(
+                &format!("P2 {}", self.second_score),
+                250.0 * text_scale,
+                strip_y,
+                30.0 * text_scale,
+                self.palette.text,
+            );
+        }
+        
+        // Whether player one can cut off their tail yet, under the wrap count
+        if matches!(self.state, GameState::Playing | GameState::Paused) && self.playback.is_none() {
+            let now = match self.state {
+                GameState::Paused => self.paused_at,
+                _ => self.clock.now(),
+            };
+            let waiting = self.sacrifice_ready_at - now;
+            let (cut_text, color) = if self.snake.body.len() <= SACRIFICE_SEGMENTS {
+                ("CUT: too short".to_owned(), self.palette.text)
+            } else if waiting > 0.0 {
+                (format!("CUT: {:.0}s", waiting.ceil()), self.palette.text)
+            } else {
+                (format!("CUT: {:?} ready", self.bindings.key(Action::Sacrifice)), self.palette.highlight)
+            };
+            let cut_width = text::measure(&cut_text, 24.0 * text_scale).width;
+            text::draw(
+                &cut_text,
+                viewport.width - cut_width - 10.0 * text_scale,
+                notice_y + 30.0 * text_scale,
+                24.0 * text_scale,
+                color,
+            );
+        }
+        
+        if self.settings.wall_mode.free_wraps() > 0 {
+            let wraps_text = format!("WRAPS {}", self.wraps_remaining);
+            let wraps_width = text::measure(&wraps_text, 30.0 * text_scale).width;
+            text::draw(
+                &wraps_text,
+                viewport.width - wraps_width - 10.0 * text_scale,
+                notice_y,
+                30.0 * text_scale,
+                self.palette.text,
+            );
+        }
+        
+        let difficulty_text = self.settings.difficulty.label();
+        viewport.draw_centered_text(difficulty_text, strip_y, 30.0, self.palette.text);
+        self.draw_speed_bar(viewport);
+        
+        if matches!(self.state, GameState::Playing | GameState::Paused | GameState::GameOver | GameState::Victory) {
+            let elapsed = self.elapsed() as u64;
+            let stats_text = format!(
+                "{:02}:{:02}  {:.1} cells/s",
+                elapsed / 60,
+                elapsed % 60,
+                1.0 / self.tick_interval()
+            );
+            text::draw(&stats_text, 10.0 * text_scale, strip_y, 30.0 * text_scale, self.palette.text);
+        }
+        
+        if let Some(until) = self.tail_passthrough_until.filter(|_| matches!(self.state, GameState::Playing | GameState::Paused)) {
+            let remaining = match self.state {
+                GameState::Paused => until - self.paused_at,
+                _ => until - self.clock.now(),
+            };
+            let passthrough_text = format!("PASS-THROUGH {:.0}", remaining.max(0.0).ceil());
+            viewport.draw_centered_text(&passthrough_text, notice_y, 30.0, self.palette.highlight);
+        }
+        
+        let stall_now = match self.state {
+            GameState::Paused => self.paused_at,
+            _ => self.clock.now(),
+        };
+        let stall_left = self
+            .stall_remaining(stall_now)
+            .filter(|_| matches!(self.state, GameState::Playing | GameState::Paused) && self.countdown_remaining(self.clock.now()) <= 0.0);
+        if let Some(left) = stall_left.filter(|&left| left < STALL_WARNING_SECONDS) {
+            let stall_text = format!("EAT! {:.1}", left.max(0.0));
+            // Grows as the time runs down
+            let size = 30.0 + 18.0 * (1.0 - left.max(0.0) / STALL_WARNING_SECONDS) as f32;
+            viewport.draw_centered_text(&stall_text, viewport.hud_height + 120.0 * text_scale, size, self.palette.danger);
+        }
+        
+        // Flashes for as long as the burst lasts
+        if self.warp_until.is_some() && matches!(self.state, GameState::Playing | GameState::Paused) {
+            let color = match (self.clock.now() * WARP_FLASH_RATE).sin() > 0.0 {
+                true => self.palette.highlight,
+                false => self.palette.danger,
+            };
+            viewport.draw_centered_text("WARP", viewport.hud_height + 160.0 * text_scale, 48.0, color);
+        }
+        
+        // The time attack clock sits large at the top of the board
+        if let Some(left) = self.time_left.filter(|_| !matches!(self.state, GameState::Menu | GameState::Settings)) {
+            let color = match left < 10.0 {
+                true => self.palette.danger,
+                false => self.palette.text,
+            };
+            let time_text = format!("{:.1}", left);
+            viewport.draw_centered_text(&time_text, viewport.hud_height + 75.0 * text_scale, 48.0, Color { a: 1.0, ..color });
+        }
+        
+        if self.step_mode {
+            text::draw(
+                "STEP MODE - . to advance",
+                10.0 * text_scale,
+                viewport.height - 20.0 * text_scale,
+                24.0 * text_scale,
+                self.palette.highlight,
+            );
+        }
+        
+        if self.playback.is_some() {
+            text::draw("REPLAY", 10.0 * text_scale, notice_y, 30.0 * text_scale, self.palette.danger);
+        }
+        
+        if self.debug_overlay {
+            self.draw_debug_overlay(viewport);
+        }
+        
+        // Under the replay notice, so the two never overlap
+        if self.settings.mode == GameMode::Waves && !matches!(self.state, GameState::Menu | GameState::Settings) {
+            let wave_text = format!("WAVE {}", self.waves);
+            text::draw(&wave_text, 10.0 * text_scale, notice_y + 30.0 * text_scale, 24.0 * text_scale, self.palette.text);
+        }
+    }
+    
+    // Stacked up from the bottom left corner, above the step mode notice
+    fn draw_debug_overlay(&self, viewport: &Viewport) {
+        let text_scale = viewport.text_scale;
+        let head = self.snake.head();
+        let lines = [
+            format!("FPS {}", get_fps()),
+            format!("TICK {}", self.tick_count),
+            format!("INTERVAL {:.3}s", self.update_interval),
+            format!("HEAD {},{}", head.x, head.y),
+            format!("LEN {}", self.snake.body.len()),
+        ];
+        let color = Color { a: 0.7, ..self.palette.text };
+        for (index, line) in lines.iter().rev().enumerate() {
+            let y = viewport.height - (50.0 + index as f32 * 20.0) * text_scale;
+            text::draw(line, 10.0 * text_scale, y, 20.0 * text_scale, color);
+        }
+    }
+    
+    // Under the difficulty label: empty at the starting speed, full at the fastest the game gets
+    fn draw_speed_bar(&self, viewport: &Viewport) {
+        let base_interval = self.settings.base_update_interval();
+        let fill = match base_interval > MIN_UPDATE_INTERVAL {
+            true => ((base_interval - self.update_interval) / (base_interval - MIN_UPDATE_INTERVAL)).clamp(0.0, 1.0),
+            false => 1.0,
+        } as f32;
+        
+        let (width, height) = (SPEED_BAR_WIDTH * viewport.text_scale, SPEED_BAR_HEIGHT * viewport.text_scale);
+        let x = (viewport.width - width) / 2.0;
+        let y = viewport.hud_height - height - 2.0 * viewport.text_scale;
+        draw_rectangle(x, y, width, height, self.palette.grid);
+        draw_rectangle(x, y, width * fill, height, self.palette.highlight);
+    }
+    
+    fn draw_menu(&self, viewport: &Viewport) {
+        let title_text = "SNAKE";
+        
+        viewport.draw_centered_text(
+            title_text,
+            viewport.height / 2.0 - 60.0 * viewport.text_scale,
+            64.0,
+            self.palette.text,
+        );
+        if self.demo {
+            viewport.draw_centered_text(
+                "DEMO - press SPACE or an arrow key to play",
+                viewport.height / 2.0,
+                24.0,
+                self.palette.text,
+            );
+            return;
+        }
+        let mut items = MENU_ITEMS.map(str::to_owned);
+        items[1] = format!("{} {}", MENU_ITEMS[1], format_date(now_timestamp()));
+        self.draw_menu_items(viewport, &items, viewport.height / 2.0);
+        self.draw_leaderboard(viewport);
+        
+        let seed_text = match self.seed_entry.as_str() {
+            "" => "Type digits to play a chosen seed".to_owned(),
+            digits => format!("Seed: {digits}"),
+        };
+        viewport.draw_centered_text(
+            &seed_text,
+            viewport.height / 2.0 + 100.0 * viewport.text_scale,
+            24.0,
+            self.palette.text,
+        );
+    }
+    
+    // The ranked top scores down the right-hand side, with the one this round set picked out
+    fn draw_leaderboard(&self, viewport: &Viewport) {
+        let text_scale = viewport.text_scale;
+        let x = viewport.width - 220.0 * text_scale;
+        let top = viewport.hud_height + 40.0 * text_scale;
+        text::draw("TOP SCORES", x, top, 24.0 * text_scale, self.palette.text);
+        
+        let entries = self.leaderboard.entries();
+        if entries.is_empty() {
+            text::draw("None yet", x, top + 26.0 * text_scale, 20.0 * text_scale, self.palette.text);
+        }
+        for (rank, entry) in entries.iter().enumerate() {
+            let color = match self.leaderboard_rank == Some(rank) {
+                true => self.palette.highlight,
+                false => self.palette.text,
+            };
+            let line = format!("{:>2}. {:>5}  {}", rank + 1, entry.score, entry.date());
+            let y = top + (26.0 + rank as f32 * 22.0) * text_scale;
+            text::draw(&line, x, y, 20.0 * text_scale, color);
+        }
+    }
+    
+    fn draw_settings(&self, viewport: &Viewport) {
+        viewport.draw_centered_text(
+            "SETTINGS",
+            viewport.height / 2.0 - 200.0 * viewport.text_scale,
+            48.0,
+            self.palette.text,
+        );
+        let items = [
+            format!("Difficulty: < {} >", self.settings.difficulty.label()),
+            format!("Mode: < {} >", self.settings.mode.label()),
+            format!("Walls: < {} >", self.settings.wall_mode.label()),
+            format!("Portals: < {} >", on_off(self.settings.portals)),
+            format!("Speed: < {} >", self.settings.speed.label()),
+            format!("Adaptive speed: < {} >", on_off(self.settings.adaptive_speed)),
+            format!("Food: < {} >", self.settings.food_count),
+            format!("Runaway food: < {} >", on_off(self.settings.runaway_food)),
+            format!("Big head food: < {} >", on_off(self.settings.big_head_food)),
+            format!("Board: < {} >", self.settings.board_size.label()),
+            format!("Palette: < {} >", self.settings.palette.label()),
+            format!("Cell gap: < {} >", self.settings.segment_padding_label()),
+            format!("Food pop-in: < {} >", self.settings.food_grow_time_label()),
+            format!("Apple sprite: < {} >", on_off(self.settings.food_sprite)),
+            format!("Players: < {} >", self.settings.players.label()),
+            format!("Hold to steer: < {} >", on_off(self.settings.hold_to_steer)),
+            format!("Controls: < {} >", self.settings.control_scheme.label()),
+            format!("Danger warning: < {} >", on_off(self.settings.danger_warning)),
+            format!("Goal: < {} >", self.settings.win_target.label()),
+            format!("Stall limit: < {} >", self.settings.stall_limit_label()),
+            format!(
+                "Wrong pick: < {} >",
+                match self.settings.wrong_pick_ends_run {
+                    true => "Game over",
+                    false => "Shrink",
+                }
+            ),
+            format!(
+                "Warp speed in {}: < {} >",
+                self.settings.mode.label(),
+                on_off(self.settings.warp())
+            ),
+            format!("Confirm restart: < {} >", on_off(self.settings.confirm_restart)),
+            format!("Practice: < {} >", on_off(self.settings.practice)),
+            format!("Volume: < {} >", self.settings.volume_label()),
+            "Back".to_owned(),
+        ];
+        self.draw_menu_items(viewport, &items, viewport.height / 2.0 - 150.0 * viewport.text_scale);
+    }
+    
+    fn draw_menu_items(&self, viewport: &Viewport, items: &[String], top: f32) {
+        let first = self
+            .menu_selection
+            .saturating_sub(MENU_VISIBLE_ROWS / 2)
+            .min(items.len().saturating_sub(MENU_VISIBLE_ROWS));
+        for (index, item) in items.iter().enumerate().skip(first).take(MENU_VISIBLE_ROWS) {
+            let y = top + (index - first) as f32 * 32.0 * viewport.text_scale;
+            if index == self.menu_selection {
+                viewport.draw_centered_text(&format!("> {} <", item), y, 28.0, self.palette.highlight);
+            } else {
+                viewport.draw_centered_text(item, y, 28.0, self.palette.text);
+            }
+        }
+    }
+    
+    fn draw_countdown(&self, viewport: &Viewport) {
+        let elapsed = self.clock.now() - self.countdown_started_at;
+        let countdown_text = if elapsed < COUNTDOWN_SECONDS {
+            format!("{}", (COUNTDOWN_SECONDS - elapsed).ceil())
+        } else if elapsed < COUNTDOWN_SECONDS + GO_DISPLAY_SECONDS {
+            "GO".to_owned()
+        } else {
+            return;
+        };
+        
+        viewport.draw_centered_text(&countdown_text, viewport.height / 2.0, 96.0, self.palette.text);
+    }
+    
+    fn draw_paused(&self, viewport: &Viewport) {
+        draw_rectangle(0.0, 0.0, viewport.width, viewport.height, Color::from_rgba(0, 0, 0, 100));
+        viewport.draw_centered_text("PAUSED", viewport.height / 2.0, 48.0, self.palette.text);
+        viewport.draw_centered_text(
+            "Press ESC to quit",
+            viewport.height / 2.0 + 40.0 * viewport.text_scale,
+            24.0,
+            self.palette.text,
+        );
+    }
+    
+    fn draw_game_over(&self, viewport: &Viewport) {
+        if self.beat_record {
+            viewport.draw_centered_text(
+                "NEW HIGH SCORE!",
+                viewport.height / 2.0 - 80.0 * viewport.text_scale,
+                36.0,
+                self.palette.highlight,
+            );
+        }
+        
+        // A win gets the same screen with its own headline
+        let (game_over_text, title_color) = match self.state {
+            GameState::Victory => ("YOU WIN!", self.palette.highlight),
+            _ if self.timed_out && self.time_left == Some(0.0) => ("TIME UP", self.palette.text),
+            _ if self.timed_out => ("STARVED", self.palette.text),
+            _ => ("GAME OVER", self.palette.text),
+        };
+        let (restart_text, restart_color) = match self.can_replay() {
+            _ if self.restart_pending() => ("Press SPACE again to restart", self.palette.highlight),
+            false => ("Press SPACE to restart", self.palette.text),
+            true => ("Press SPACE to restart or R to watch the replay", self.palette.text),
+        };
+        
+        viewport.draw_centered_text(
+            game_over_text,
+            viewport.height / 2.0 - 20.0 * viewport.text_scale,
+            48.0,
+            title_color,
+        );
+        
+        // Summary of the run that just ended, one stat per line
+        let survived = self.elapsed() as u64;
+        let stats = [
+            format!("Score: {}", self.score),
+            format!("Length: {}", self.snake.body.len()),
+            format!("Time: {:02}:{:02}", survived / 60, survived % 60),
+            format!("Food eaten: {}", self.food_eaten),
+            match self.daily {
+                true => format!("Daily seed: {} - compare scores with friends!", self.recording.seed),
+                false => format!("Seed: {}", self.recording.seed),
+            },
+        ];
+        for (index, stat) in stats.iter().enumerate() {
+            let y = viewport.height / 2.0 + (20.0 + index as f32 * 26.0) * viewport.text_scale;
+            viewport.draw_centered_text(stat, y, 24.0, self.palette.text);
+        }
+        
+        viewport.draw_centered_text(
+            restart_text,
+            viewport.height / 2.0 + 160.0 * viewport.text_scale,
+            24.0,
+            restart_color,
+        );
+        
+        self.draw_leaderboard(viewport);
+        
+        if self.second_snake.is_some() {
+            let scores_text = format!("P1 {} - P2 {}", self.score, self.second_score);
+            viewport.draw_centered_text(
+                &scores_text,
+                viewport.height / 2.0 + 200.0 * viewport.text_scale,
+                24.0,
+                self.palette.text,
+            );
+        }
+    }
+}
+
+// The cells between `from` and `to`, leaving out both ends: along the row first, then down the column
+fn breadcrumb_path(from: Position, to: Position) -> Vec<Position> {
+    let step_x = (to.x - from.x).signum();
+    let step_y = (to.y - from.y).signum();
+    let across = (1..=(to.x - from.x).abs()).map(|offset| Position::new(from.x + offset * step_x, from.y));
+    let down = (1..=(to.y - from.y).abs()).map(|offset| Position::new(to.x, from.y + offset * step_y));
+    let mut path: Vec<Position> = across.chain(down).collect();
+    path.pop();
+    path
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::level::parse_level;
+    use crate::settings::{BoardSize, Difficulty};
+    
+    // A running game on the default board whose snake has a clear row ahead for a few seconds
+    fn game(clock: &ManualClock) -> Game {
+        game_with(clock, Settings::default())
+    }
+    
+    fn game_with(clock: &ManualClock, settings: Settings) -> Game {
+        let settings = Settings {
+            adaptive_speed: false,
+            ..settings
+        };
+        let mut game = Game::with_clock(settings, Sounds::default(), Some(1), None, None, Box::new(clock.clone()));
+        game.foods[0].position = Position::new(0, 0);
+        game.foods[0].kind = FoodKind::Normal;
+        game.start();
+        // Sit out the countdown without running a frame, so none of it is banked
+        clock.advance(COUNTDOWN_SECONDS);
+        game
+    }
+    
+    fn run_frames(game: &mut Game, clock: &ManualClock, frame_time: f64, frames: usize) {
+        for _ in 0..frames {
+            clock.advance(frame_time);
+            game.update();
+        }
+    }
+    
+    #[test]
+    fn ticks_follow_the_clock_not_the_frame_rate() {
+        for fps in [30, 60, 144] {
+            let clock = ManualClock::default();
+            let mut game = game(&clock);
+            let start = game.snake.head();
+            
+            run_frames(&mut game, &clock, 1.0 / fps as f64, fps * 3);
+            
+            // Three seconds at the normal 0.15s interval
+            assert_eq!(game.tick_count, 20, "at {fps} fps");
+            assert_eq!(game.snake.head(), Position::new(start.x + 20, start.y));
+        }
+    }
+    
+    #[test]
+    fn boost_ticks_faster_but_not_past_the_floor() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        game.set_boosting(true);
+        run_frames(&mut game, &clock, 1.0 / 60.0, 90);
+        // Half the normal 0.15s interval for a second and a half
+        assert_eq!(game.tick_count, 20);
+        
+        game.update_interval = MIN_UPDATE_INTERVAL;
+        assert_eq!(game.tick_interval(), MIN_UPDATE_INTERVAL);
+    }
+    
+    #[test]
+    fn every_food_item_gets_its_own_free_cell() {
+        let settings = Settings {
+            food_count: 5,
+            ..Settings::default()
+        };
+        let game = Game::with_clock(settings, Sounds::default(), Some(3), None, None, Box::new(ManualClock::default()));
+        
+        let cells: HashSet<Position> = game.foods.iter().map(|food| food.position).collect();
+        assert_eq!(cells.len(), 5);
+        assert!(cells.iter().all(|cell| !game.snake.body.contains(cell) && !game.obstacles.contains(cell)));
+    }
+    
+    #[test]
+    fn tail_passthrough_wears_off_on_easy() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        game.settings.difficulty = Difficulty::Easy;
+        game.start();
+        clock.advance(COUNTDOWN_SECONDS);
+        assert!(game.tail_passthrough_until.is_some());
+        
+        run_frames(&mut game, &clock, 0.05, 120);
+        
+        assert!(game.tail_passthrough_until.is_none());
+        assert_eq!(game.recording.passthrough_ended.map(|tick| tick > 0), Some(true));
+    }
+    
+    #[test]
+    fn time_attack_ends_when_the_clock_runs_out() {
+        let clock = ManualClock::default();
+        let settings = Settings {
+            mode: GameMode::TimeAttack,
+            time_budget: 1.0,
+            ..Settings::default()
+        };
+        let mut game = game_with(&clock, settings);
+        
+        run_frames(&mut game, &clock, 0.05, 19);
+        assert!(game.state == GameState::Playing);
+        run_frames(&mut game, &clock, 0.05, 10);
+        
+        assert!(game.state == GameState::GameOver);
+        assert!(game.timed_out);
+        assert_eq!(game.recording.timed_out, Some(game.tick_count));
+    }
+    
+    #[test]
+    fn a_new_feast_cluster_only_comes_once_the_last_is_eaten() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { mode: GameMode::Feast, ..Settings::default() });
+        assert_eq!(game.foods.len(), FEAST_CLUSTER_SIZE);
+        
+        let first = game.foods[0].position;
+        game.respawn_food(0, 0.0);
+        assert_eq!(game.foods.len(), FEAST_CLUSTER_SIZE - 1);
+        assert!(game.foods.iter().all(|food| food.position != first));
+        
+        while game.foods.len() > 1 {
+            game.respawn_food(0, 0.0);
+        }
+        game.respawn_food(0, 0.0);
+        assert_eq!(game.foods.len(), FEAST_CLUSTER_SIZE);
+    }
+    
+    #[test]
+    fn picking_the_poison_of_a_risk_pair_ends_the_round() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { mode: GameMode::Risk, ..Settings::default() });
+        assert_eq!(game.foods.len(), 2);
+        assert!(game.foods.iter().all(|food| food.pair));
+        
+        // Eating the normal one brings a fresh pair
+        let next = game.snake.next_head(&game.config, game.settings.wall_mode, &game.portals);
+        game.foods[0].kind = FoodKind::Normal;
+        game.foods[0].position = next;
+        game.foods[1].kind = FoodKind::Poison;
+        game.foods[1].position = Position::new(0, 20);
+        let tick = game.tick_count;
+        while game.tick_count == tick {
+            run_frames(&mut game, &clock, 0.05, 1);
+        }
+        assert_eq!(game.score, 1);
+        assert_eq!(game.foods.len(), 2);
+        assert_eq!(game.foods.iter().filter(|food| food.kind == FoodKind::Poison).count(), 1);
+        
+        let next = game.snake.next_head(&game.config, game.settings.wall_mode, &game.portals);
+        let poison = game.foods.iter().position(|food| food.kind == FoodKind::Poison).unwrap();
+        game.foods[poison].position = next;
+        game.foods[1 - poison].position = Position::new(0, 20);
+        run_frames(&mut game, &clock, 0.05, 10);
+        assert!(game.state == GameState::GameOver);
+    }
+    
+    #[test]
+    fn waves_drop_obstacles_on_free_cells_until_restart() {
+        let clock = ManualClock::default();
+        let settings = Settings {
+            mode: GameMode::Waves,
+            wall_mode: WallMode::Wrap,
+            ..Settings::default()
+        };
+        let mut game = game_with(&clock, settings);
+        let layout = game.obstacles.clone();
+        game.foods[0].position = Position::new(0, 20);
+        
+        run_frames(&mut game, &clock, 0.05, (WAVE_SECONDS * 2.0 / 0.05) as usize + 1);
+        assert_eq!(game.waves, 2);
+        assert_eq!(game.obstacles.len(), layout.len() + 2);
+        for cell in &game.obstacles[layout.len()..] {
+            assert!(!game.snake.body.contains(cell));
+            assert_ne!(*cell, game.foods[0].position);
+        }
+        
+        game.restart(None);
+        assert_eq!(game.waves, 0);
+        assert_eq!(game.obstacles, layout);
+    }
+    
+    #[test]
+    fn freeze_food_holds_runaway_food_and_the_wave_timer_until_it_wears_off() {
+        let clock = ManualClock::default();
+        let settings = Settings {
+            mode: GameMode::Waves,
+            wall_mode: WallMode::Wrap,
+            runaway_food: true,
+            ..Settings::default()
+        };
+        let mut game = game_with(&clock, settings);
+        let next = game.snake.next_head(&game.config, game.settings.wall_mode, &game.portals);
+        game.foods[0].kind = FoodKind::Freeze;
+        game.foods[0].position = next;
+        let tick = game.tick_count;
+        while game.tick_count == tick {
+            run_frames(&mut game, &clock, 0.05, 1);
+        }
+        assert_eq!(game.freeze_ticks, FREEZE_TICKS);
+        let wave_timer = game.last_wave_at;
+        
+        game.foods[0].kind = FoodKind::Normal;
+        game.foods[0].position = Position::new(0, 20);
+        while game.freeze_ticks > 1 {
+            run_frames(&mut game, &clock, 0.05, 1);
+            assert_eq!(game.foods[0].position, Position::new(0, 20));
+        }
+        assert_eq!(game.last_wave_at, wave_timer);
+        
+        // Thawing moves the wave timer on by the time spent frozen, and the food gets going again
+        while game.freeze_ticks > 0 {
+            run_frames(&mut game, &clock, 0.05, 1);
+        }
+        assert!(game.last_wave_at > wave_timer);
+        run_frames(&mut game, &clock, 0.05, 40);
+        assert_ne!(game.foods[0].position, Position::new(0, 20));
+        assert!(game.state == GameState::Playing);
+    }
+    
+    #[test]
+    fn the_same_seed_lays_out_the_same_wave_obstacles() {
+        let layout = |seed| {
+            let clock = ManualClock::default();
+            let settings = Settings {
+                mode: GameMode::Waves,
+                wall_mode: WallMode::Wrap,
+                ..Settings::default()
+            };
+            let mut game = game_with(&clock, settings);
+            game.restart(Some(seed));
+            game.foods[0].kind = FoodKind::Normal;
+            game.foods[0].position = Position::new(0, 20);
+            clock.advance(COUNTDOWN_SECONDS);
+            run_frames(&mut game, &clock, 0.05, (WAVE_SECONDS * 3.0 / 0.05) as usize + 1);
+            assert_eq!(game.waves, 3);
+            game.obstacles
+        };
+        
+        assert_eq!(layout(7), layout(7));
+        assert_ne!(layout(7), layout(8));
+    }
+    
+    #[test]
+    fn practice_crashes_rewind_the_snake_instead_of_ending_the_round() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { practice: true, ..Settings::default() });
+        game.foods[0].position = Position::new(0, 20);
+        let to_wall = (game.config.cell_number_x - game.snake.head().x) as u64;
+        while game.tick_count < to_wall {
+            run_frames(&mut game, &clock, 0.05, 1);
+        }
+        assert!(game.state == GameState::Playing);
+        assert!(game.snake.alive);
+        assert!(game.rewound_at.is_some());
+        let rewound_to = game.config.cell_number_x - REWIND_TICKS as i32;
+        assert_eq!(game.snake.head(), Position::new(rewound_to, 10));
+        
+        // Steering clear gets the run going again, and when it does end nothing is recorded
+        game.steer(Direction::Up);
+        run_frames(&mut game, &clock, 0.05, 10);
+        assert!(game.snake.alive);
+        game.score = game.high_score + 10;
+        game.end_round(GameState::GameOver, clock.now());
+        assert!(!game.beat_record);
+        assert_eq!(game.leaderboard_rank, None);
+    }
+    
+    #[test]
+    fn a_practice_rewind_skips_back_past_an_arena_shrink() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { practice: true, mode: GameMode::Arena, ..Settings::default() });
+        game.foods[0].position = Position::new(30, 20);
+        let tick = |game: &mut Game| {
+            let tick = game.tick_count;
+            while game.tick_count == tick {
+                run_frames(game, &clock, 0.05, 1);
+            }
+        };
+        
+        // Along the top row for longer than a rewind reaches back, then down off it before it's walled off
+        game.steer(Direction::Up);
+        while game.snake.head().y > 0 {
+            tick(&mut game);
+        }
+        game.steer(Direction::Right);
+        for _ in 0..REWIND_TICKS {
+            tick(&mut game);
+        }
+        game.steer(Direction::Down);
+        for _ in 0..3 {
+            tick(&mut game);
+        }
+        game.last_shrink_at = clock.now() - ARENA_SHRINK_SECONDS;
+        tick(&mut game);
+        assert_eq!(game.bounds.top, 1);
+        let next = game.snake.next_head(&game.config, game.settings.wall_mode, &game.portals);
+        game.obstacles.push(next);
+        tick(&mut game);
+        
+        // The oldest snapshots still have the body on the top row, so it goes back to the first clear one
+        assert!(game.rewound_at.is_some());
+        assert!(game.snake.alive);
+        assert!(game.fits_board(&game.snake));
+        assert_eq!(game.snake.head().y, 3);
+        
+        let rewound_at = game.rewound_at;
+        game.steer(Direction::Right);
+        for _ in 0..10 {
+            tick(&mut game);
+        }
+        assert!(game.state == GameState::Playing);
+        assert!(game.snake.alive);
+        assert_eq!(game.rewound_at, rewound_at);
+    }
+    
+    #[test]
+    fn a_two_player_round_is_never_played_back() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { players: PlayerCount::Two, ..Settings::default() });
+        game.end_round(GameState::GameOver, clock.now());
+        game.start_replay();
+        
+        assert!(game.playback.is_none());
+        assert!(game.state == GameState::GameOver);
+    }
+    
+    #[test]
+    fn a_replay_never_sets_a_record() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        let high_score = game.high_score;
+        game.playback = Some(Replay::default());
+        game.score = high_score + 10;
+        game.end_round(GameState::GameOver, clock.now());
+        
+        assert!(!game.beat_record);
+        assert_eq!(game.high_score, high_score);
+        assert_eq!(game.leaderboard_rank, None);
+        assert!(game.best_ghost.is_none());
+    }
+    
+    #[test]
+    fn the_combo_window_is_counted_in_ticks_whatever_the_speed() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { wall_mode: WallMode::Wrap, ..Settings::default() });
+        let eat_next = |game: &mut Game| {
+            let next = game.snake.next_head(&game.config, game.settings.wall_mode, &game.portals);
+            game.foods[0].kind = FoodKind::Normal;
+            game.foods[0].position = next;
+            game.tick(clock.now());
+        };
+        
+        eat_next(&mut game);
+        assert_eq!(game.combo, 0);
+        // However long the ticks take in seconds, the next meal within the window keeps the streak
+        clock.advance(60.0);
+        eat_next(&mut game);
+        assert_eq!(game.combo, 1);
+        
+        game.foods[0].position = Position::new(0, 20);
+        for _ in 0..COMBO_WINDOW_TICKS {
+            game.tick(clock.now());
+        }
+        eat_next(&mut game);
+        assert_eq!(game.combo, 0);
+    }
+    
+    #[test]
+    fn every_built_in_board_starts_with_room_to_move() {
+        for board_size in [BoardSize::Small, BoardSize::Medium, BoardSize::Large] {
+            for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+                for players in [PlayerCount::One, PlayerCount::Two] {
+                    for portals in [false, true] {
+                        let clock = ManualClock::default();
+                        let settings = Settings { board_size, difficulty, players, portals, ..Settings::default() };
+                        let mut game = game_with(&clock, settings);
+                        game.tick(clock.now());
+                        
+                        let case = format!("{board_size:?} {difficulty:?} {players:?} portals {portals}");
+                        assert!(game.state == GameState::Playing, "{case}");
+                        assert!(game.snakes().all(|snake| snake.alive), "{case}");
+                    }
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn custom_boards_move_a_blocked_start_somewhere_clear() {
+        // Player two would start mirrored onto the wall in the bottom left
+        let level = parse_level("#######\n#..>..#\n#.....#\n#....F#\n#.#...#\n#######").unwrap();
+        let clock = ManualClock::default();
+        let settings = Settings { players: PlayerCount::Two, ..Settings::default() };
+        let game = Game::with_clock(settings, Sounds::default(), Some(1), Some(level), None, Box::new(clock.clone()));
+        let second = game.second_snake.as_ref().unwrap();
+        assert!(game.fits_board(&game.snake) && game.fits_board(second));
+        assert_ne!(game.snake.head(), Position::new(3, 1));
+        
+        // A level with no room for two is played by player one from where it says
+        let level = parse_level("######\n#..>.#\n#F#..#\n######").unwrap();
+        let game = Game::with_clock(settings, Sounds::default(), Some(1), Some(level), None, Box::new(clock.clone()));
+        assert!(game.second_snake.is_none());
+        assert_eq!(game.snake.head(), Position::new(3, 1));
+        
+        // The smallest boards the command line allows, on the busiest layout
+        let settings = Settings { players: PlayerCount::Two, difficulty: Difficulty::Hard, ..Settings::default() };
+        let mut game = Game::with_clock(settings, Sounds::default(), Some(1), None, Some((12, 12)), Box::new(clock.clone()));
+        game.start();
+        clock.advance(COUNTDOWN_SECONDS);
+        game.tick(clock.now());
+        assert!(game.state == GameState::Playing);
+        assert!(game.snakes().all(|snake| snake.alive));
+    }
+    
+    #[test]
+    fn going_hungry_past_the_stall_limit_ends_the_round() {
+        let clock = ManualClock::default();
+        let settings = Settings {
+            stall_limit: Some(10),
+            wall_mode: WallMode::Wrap,
+            ..Settings::default()
+        };
+        let mut game = game_with(&clock, settings);
+        // Keep the food out of the snake's path
+        game.foods[0].position = Position::new(0, 20);
+        
+        run_frames(&mut game, &clock, 0.05, 190);
+        assert!(game.state == GameState::Playing);
+        run_frames(&mut game, &clock, 0.05, 20);
+        assert!(game.state == GameState::GameOver);
+        assert!(game.timed_out);
+    }
+    
+    #[test]
+    fn sacrificing_the_tail_costs_segments_and_points() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { wall_mode: WallMode::Wrap, ..Settings::default() });
+        game.foods[0].position = Position::new(0, 20);
+        // Grow a segment a tick until there's a tail to spare
+        while game.snake.body.len() <= SACRIFICE_SEGMENTS {
+            game.snake.grow();
+            let tick = game.tick_count;
+            while game.tick_count == tick {
+                run_frames(&mut game, &clock, 0.05, 1);
+            }
+        }
+        game.score = 3;
+        let length = game.snake.body.len();
+        
+        assert!(game.sacrifice_tail());
+        assert_eq!(game.snake.body.len(), length - SACRIFICE_SEGMENTS);
+        assert_eq!(game.score, 0);
+        // Too short to spare another cut, and the score is left alone
+        game.score = 2;
+        assert!(!game.sacrifice_tail());
+        assert_eq!(game.score, 2);
+    }
+    
+    #[test]
+    fn a_confirmed_restart_needs_a_second_press_in_time() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { confirm_restart: true, ..Settings::default() });
+        
+        assert!(!game.confirm_restart());
+        clock.advance(RESTART_CONFIRM_SECONDS / 2.0);
+        assert!(game.confirm_restart());
+        
+        // Too slow, so the second press only arms it again
+        game.restart_requested_at = None;
+        assert!(!game.confirm_restart());
+        clock.advance(RESTART_CONFIRM_SECONDS + 0.1);
+        assert!(!game.restart_pending());
+        assert!(!game.confirm_restart());
+        
+        game.settings.confirm_restart = false;
+        assert!(game.confirm_restart());
+    }
+    
+    #[test]
+    fn relative_controls_turn_about_the_heading() {
+        let clock = ManualClock::default();
+        let mut game = game_with(&clock, Settings { control_scheme: ControlScheme::Relative, ..Settings::default() });
+        
+        // The snake starts heading right
+        assert_eq!(game.key_direction(Direction::Left, &game.snake), Some(Direction::Up));
+        assert_eq!(game.key_direction(Direction::Right, &game.snake), Some(Direction::Down));
+        assert_eq!(game.key_direction(Direction::Up, &game.snake), None);
+        
+        // A second turn builds on the buffered one
+        game.steer(Direction::Down);
+        assert_eq!(game.key_direction(Direction::Right, &game.snake), Some(Direction::Left));
+        
+        game.settings.control_scheme = ControlScheme::Absolute;
+        assert_eq!(game.key_direction(Direction::Up, &game.snake), Some(Direction::Up));
+    }
+    
+    #[test]
+    fn the_breadcrumb_path_runs_along_the_row_then_the_column() {
+        let path = breadcrumb_path(Position::new(2, 2), Position::new(4, 0));
+        assert_eq!(path, [Position::new(3, 2), Position::new(4, 2), Position::new(4, 1)]);
+        assert_eq!(breadcrumb_path(Position::new(1, 1), Position::new(0, 1)), []);
+        assert_eq!(breadcrumb_path(Position::new(1, 1), Position::new(1, 1)), []);
+    }
+    
+    #[test]
+    fn warp_speed_bursts_past_the_top_speed_then_settles() {
+        let clock = ManualClock::default();
+        let mut settings = Settings { wall_mode: WallMode::Wrap, ..Settings::default() };
+        settings.toggle_warp();
+        let mut game = game_with(&clock, settings);
+        game.foods[0].position = Position::new(0, 20);
+        
+        game.score = WARP_SCORE_STEP - 1;
+        game.check_warp(clock.now());
+        assert!(game.warp_until.is_none());
+        
+        game.update_interval = MIN_UPDATE_INTERVAL;
+        game.food_eaten = 1_000;
+        game.score = WARP_SCORE_STEP + 5;
+        game.check_warp(clock.now());
+        game.recompute_speed();
+        assert!(game.update_interval < MIN_UPDATE_INTERVAL);
+        assert!(game.update_interval >= WARP_MIN_INTERVAL);
+        assert_eq!(game.next_warp_score, WARP_SCORE_STEP * 2);
+        
+        run_frames(&mut game, &clock, 0.05, (WARP_SECONDS / 0.05) as usize + 1);
+        assert!(game.warp_until.is_none());
+        assert_eq!(game.update_interval, MIN_UPDATE_INTERVAL);
+    }
+    
+    #[test]
+    fn step_mode_ticks_once_per_request() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        game.step_mode = true;
+        
+        run_frames(&mut game, &clock, 0.5, 4);
+        assert_eq!(game.tick_count, 0);
+        
+        game.step_requested = true;
+        run_frames(&mut game, &clock, 0.5, 4);
+        assert_eq!(game.tick_count, 1);
+    }
+    
+    #[test]
+    fn a_long_pause_leaves_the_round_timers_where_they_were() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        run_frames(&mut game, &clock, 0.05, 6);
+        let played = clock.now() - game.run_started_at;
+        let food_age = clock.now() - game.foods[0].spawned_at;
+        
+        game.pause();
+        clock.advance(300.0);
+        game.resume();
+        
+        assert!((clock.now() - game.run_started_at - played).abs() < 1e-9);
+        assert!((clock.now() - game.foods[0].spawned_at - food_age).abs() < 1e-9);
+        assert!(game.state == GameState::Playing);
+    }
+    
+    #[test]
+    fn time_spent_paused_does_not_tick() {
+        let clock = ManualClock::default();
+        let mut game = game(&clock);
+        
+        run_frames(&mut game, &clock, 0.05, 6);
+        game.pause();
+        run_frames(&mut game, &clock, 0.05, 100);
+        game.resume();
+        run_frames(&mut game, &clock, 0.05, 6);
+        
+        assert_eq!(game.tick_count, 4);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::snake::Direction;
+
+#[cfg(feature = "gamepad")]
+const STICK_DEADZONE: f32 = 0.5;
+
+// Controller actions read once per frame, mirroring the keyboard controls
+#[derive(Clone, Copy, Default)]
+pub struct GamepadInput {
+    pub direction: Option<Direction>,
+    pub start: bool,
+}
+
+#[cfg(feature = "gamepad")]
+pub struct Gamepad {
+    gamepads: gamepads::Gamepads,
+    last_stick_direction: Option<Direction>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepad {
+    pub fn new() -> Self {
+        Self {
+            gamepads: gamepads::Gamepads::new(),
+            last_stick_direction: None,
+        }
+    }
+    
+    pub fn poll(&mut self) -> GamepadInput {
+        use gamepads::Button;
+        
+        self.gamepads.poll();
+        
+        let mut input = GamepadInput::default();
+        let mut stick_direction = None;
+        for pad in self.gamepads.all() {
+            if pad.is_just_pressed(Button::DPadUp) {
+                input.direction = Some(Direction::Up);
+            } else if pad.is_just_pressed(Button::DPadDown) {
+                input.direction = Some(Direction::Down);
+            } else if pad.is_just_pressed(Button::DPadLeft) {
+                input.direction = Some(Direction::Left);
+            } else if pad.is_just_pressed(Button::DPadRight) {
+                input.direction = Some(Direction::Right);
+            }
+            
+            if pad.is_just_pressed(Button::ActionDown) || pad.is_just_pressed(Button::RightCenterCluster) {
+                input.start = true;
+            }
+            
+            let (x, y) = pad.left_stick();
+            stick_direction = stick_direction.or(Self::stick_direction(x, y));
+        }
+        
+        // Only a stick moving into a new direction counts as a turn, like a key press
+        if stick_direction != self.last_stick_direction {
+            input.direction = input.direction.or(stick_direction);
+            self.last_stick_direction = stick_direction;
+        }
+        
+        input
+    }
+    
+    fn stick_direction(x: f32, y: f32) -> Option<Direction> {
+        if x.abs() < STICK_DEADZONE && y.abs() < STICK_DEADZONE {
+            return None;
+        }
+        
+        // Stick y is positive upwards
+        if x.abs() > y.abs() {
+            Some(if x > 0.0 { Direction::Right } else { Direction::Left })
+        } else {
+            Some(if y > 0.0 { Direction::Up } else { Direction::Down })
+        }
+    }
+}
+
+// Without controller support the gamepad never reports any input
+#[cfg(not(feature = "gamepad"))]
+pub struct Gamepad;
+
+#[cfg(not(feature = "gamepad"))]
+impl Gamepad {
+    pub fn new() -> Self {
+        Self
+    }
+    
+    pub fn poll(&mut self) -> GamepadInput {
+        GamepadInput::default()
+    }
+}
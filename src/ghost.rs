@@ -0,0 +1,45 @@
+use crate::types::Position;
+
+// Player one's path through a run, one entry per tick, so the run can be shown again as a ghost snake
+#[derive(Clone, Default)]
+pub struct Ghost {
+    heads: Vec<Position>,
+    lengths: Vec<usize>,
+}
+
+impl Ghost {
+    // Called once per tick, before the snake moves, so entry `n` is the snake after `n` ticks
+    pub fn record(&mut self, head: Position, length: usize) {
+        self.heads.push(head);
+        self.lengths.push(length);
+    }
+    
+    // The ghost's body after `ticks` ticks, head first; empty once the recorded run is over.
+    // The body is just the trail of the head, so only the head is known before the first moves
+    pub fn body_at(&self, ticks: u64) -> impl Iterator<Item = Position> + '_ {
+        let index = ticks as usize;
+        let (trail, length) = match self.lengths.get(index) {
+            Some(&length) => (&self.heads[..=index], length),
+            None => (&self.heads[..0], 0),
+        };
+        trail.iter().rev().take(length).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn the_body_trails_behind_the_recorded_head() {
+        let mut ghost = Ghost::default();
+        for x in 0..5 {
+            ghost.record(Position::new(x, 2), 3);
+        }
+        
+        let body: Vec<Position> = ghost.body_at(3).collect();
+        assert_eq!(body, [Position::new(3, 2), Position::new(2, 2), Position::new(1, 2)]);
+        assert_eq!(ghost.body_at(0).count(), 1);
+        assert_eq!(ghost.body_at(5).count(), 0);
+    }
+}
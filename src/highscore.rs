@@ -0,0 +1,171 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HIGH_SCORE_FILE: &str = "highscore.txt";
+const LEADERBOARD_FILE: &str = "leaderboard.txt";
+pub const LEADERBOARD_SIZE: usize = 10;
+
+pub fn load_high_score() -> u32 {
+    fs::read_to_string(HIGH_SCORE_FILE)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn save_high_score(score: u32) {
+    // Losing the high score isn't worth interrupting the game over
+    let _ = fs::write(HIGH_SCORE_FILE, score.to_string());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    pub score: u32,
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl Entry {
+    // The day the score was set, as YYYY-MM-DD in UTC
+    pub fn date(self) -> String {
+        format_date(self.timestamp)
+    }
+}
+
+// The best `LEADERBOARD_SIZE` scores, highest first
+#[derive(Debug, Default, PartialEq)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+    
+    // Rank `score` among the others, returning where it landed if it made the list. Ties go below the
+    // scores already there, and a run that scored nothing isn't listed
+    pub fn insert(&mut self, score: u32, timestamp: u64) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+        let rank = self.entries.iter().position(|entry| entry.score < score).unwrap_or(self.entries.len());
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        self.entries.insert(rank, Entry { score, timestamp });
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+    
+    // One `score timestamp` line per entry; lines that don't read as that are dropped
+    fn parse(contents: &str) -> Self {
+        let mut leaderboard = Leaderboard::default();
+        for line in contents.lines() {
+            let mut fields = line.split_whitespace().map(str::parse::<u64>);
+            if let (Some(Ok(score)), Some(Ok(timestamp)), None) = (fields.next(), fields.next(), fields.next()) {
+                if let Ok(score) = u32::try_from(score) {
+                    leaderboard.insert(score, timestamp);
+                }
+            }
+        }
+        leaderboard
+    }
+    
+    fn to_file(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{} {}\n", entry.score, entry.timestamp))
+            .collect()
+    }
+}
+
+pub fn load_leaderboard() -> Leaderboard {
+    fs::read_to_string(LEADERBOARD_FILE)
+        .map(|contents| Leaderboard::parse(&contents))
+        .unwrap_or_default()
+}
+
+pub fn save_leaderboard(leaderboard: &Leaderboard) {
+    let _ = fs::write(LEADERBOARD_FILE, leaderboard.to_file());
+}
+
+// Year, month and day in UTC of a Unix timestamp
+fn civil_date(timestamp: u64) -> (i64, i64, i64) {
+    // Civil-from-days, counting in 400-year eras that start on 1 March so leap days fall last
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// The daily challenge's seed: the UTC date read as YYYYMMDD, so everyone playing that day gets the
+// same board and the seed on the game over screen says which day it was
+pub fn daily_seed(timestamp: u64) -> u64 {
+    let (year, month, day) = civil_date(timestamp);
+    (year * 10_000 + month * 100 + day) as u64
+}
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn scores_are_ranked_and_trimmed() {
+        let mut leaderboard = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u32 {
+            leaderboard.insert(score * 10, 0);
+        }
+        
+        assert_eq!(leaderboard.insert(55, 1), Some(5));
+        assert_eq!(leaderboard.entries().len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries()[5], Entry { score: 55, timestamp: 1 });
+        assert_eq!(leaderboard.entries().last().unwrap().score, 20);
+        // Tied with the bottom of a full list isn't enough
+        assert_eq!(leaderboard.insert(20, 2), None);
+        assert_eq!(leaderboard.insert(0, 2), None);
+    }
+    
+    #[test]
+    fn corrupt_lines_are_skipped() {
+        let leaderboard = Leaderboard::parse("30 100\nnot a score\n50 200\n-4 1\n40\n99999999999 5\n");
+        
+        let scores: Vec<u32> = leaderboard.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [50, 30]);
+        assert_eq!(Leaderboard::parse(&leaderboard.to_file()), leaderboard);
+    }
+    
+    #[test]
+    fn timestamps_read_as_dates() {
+        assert_eq!(Entry { score: 1, timestamp: 0 }.date(), "1970-01-01");
+        assert_eq!(Entry { score: 1, timestamp: 951_782_400 }.date(), "2000-02-29");
+        assert_eq!(Entry { score: 1, timestamp: 1_735_689_599 }.date(), "2024-12-31");
+    }
+    
+    #[test]
+    fn the_daily_seed_only_changes_with_the_date() {
+        assert_eq!(daily_seed(951_782_400), 20_000_229);
+        assert_eq!(daily_seed(951_782_400 + 86_399), 20_000_229);
+        assert_eq!(daily_seed(951_782_400 + 86_400), 20_000_301);
+    }
+}
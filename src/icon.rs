@@ -0,0 +1,37 @@
+use macroquad::miniquad::conf::Icon;
+use macroquad::prelude::*;
+
+const ICON_FILE: &str = "assets/icon.png";
+const ICON_SIZE: u16 = 64;
+
+// The window icon from a 64x64 image in `assets/`, with the smaller sizes sampled down from it.
+// A missing or differently sized file just leaves the platform's default icon
+pub fn load_icon() -> Option<Icon> {
+    let bytes = std::fs::read(ICON_FILE).ok()?;
+    let image = Image::from_file_with_format(&bytes, Some(ImageFormat::Png)).ok()?;
+    if image.width != ICON_SIZE || image.height != ICON_SIZE {
+        return None;
+    }
+    
+    let mut icon = Icon {
+        small: [0; 16 * 16 * 4],
+        medium: [0; 32 * 32 * 4],
+        big: [0; 64 * 64 * 4],
+    };
+    icon.big.copy_from_slice(&image.bytes);
+    downsample(&image.bytes, &mut icon.medium, 32);
+    downsample(&image.bytes, &mut icon.small, 16);
+    Some(icon)
+}
+
+// Nearest-neighbour scale of the 64x64 RGBA `source` into a `size`x`size` `target`
+fn downsample(source: &[u8], target: &mut [u8], size: usize) {
+    let step = ICON_SIZE as usize / size;
+    for y in 0..size {
+        for x in 0..size {
+            let from = ((y * step) * ICON_SIZE as usize + x * step) * 4;
+            let to = (y * size + x) * 4;
+            target[to..to + 4].copy_from_slice(&source[from..from + 4]);
+        }
+    }
+}
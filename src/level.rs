@@ -0,0 +1,214 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::snake::{Direction, Snake};
+use crate::types::{GameConfig, Position};
+
+// Segments a snake starts with; custom levels must leave room for them behind the head
+pub const START_LENGTH: usize = 3;
+
+// Static walls for each level; level 0 is the classic open board
+pub fn level_obstacles(level: u32, config: &GameConfig) -> Vec<Position> {
+    let mut obstacles = Vec::new();
+    if level == 0 {
+        return obstacles;
+    }
+    
+    // Two horizontal bars across the middle half of the board
+    let (left, right) = (config.cell_number_x / 4, config.cell_number_x * 3 / 4);
+    let (top, bottom) = (config.cell_number_y / 4, config.cell_number_y * 3 / 4);
+    for x in left..right {
+        obstacles.push(Position::new(x, top));
+        obstacles.push(Position::new(x, bottom));
+    }
+    
+    // Higher levels add a vertical divider between the bars
+    if level >= 2 {
+        for y in top + 3..bottom - 2 {
+            obstacles.push(Position::new(config.cell_number_x / 2, y));
+        }
+    }
+    
+    obstacles
+}
+
+// A pair of linked portals on the left and right of the board, clear of every obstacle layout
+pub fn portal_pairs(config: &GameConfig) -> Vec<(Position, Position)> {
+    let y = config.cell_number_y / 2;
+    vec![(
+        Position::new(config.cell_number_x / 8, y),
+        Position::new(config.cell_number_x * 7 / 8, y),
+    )]
+}
+
+// A hand-made board read from a text file, one character per cell:
+// `#` wall, `.` empty, `S` or `>` snake head facing right, `<`, `^` or `v` snake head facing left, up
+// or down (the body trails straight out behind it), `F` first food
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub width: i32,
+    pub height: i32,
+    pub obstacles: Vec<Position>,
+    pub snake_start: Position,
+    pub snake_direction: Direction,
+    pub food: Position,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Empty,
+    UnevenRow { line: usize },
+    UnknownTile { line: usize, column: usize, tile: char },
+    DuplicateTile { line: usize, column: usize, tile: char },
+    MissingTile(char),
+    // The cells behind the head that the starting body covers must be open floor on the board
+    BlockedSnakeStart,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "couldn't read level file: {error}"),
+            LevelError::Empty => write!(f, "level has no rows"),
+            LevelError::UnevenRow { line } => write!(f, "line {line}: row length differs from the first row"),
+            LevelError::UnknownTile { line, column, tile } => {
+                write!(f, "line {line}, column {column}: unknown tile '{tile}'")
+            }
+            LevelError::DuplicateTile { line, column, tile } => {
+                write!(f, "line {line}, column {column}: only one '{tile}' is allowed")
+            }
+            LevelError::MissingTile(tile) => write!(f, "level has no '{tile}' tile"),
+            LevelError::BlockedSnakeStart => {
+                write!(f, "the {} cells behind the snake's head must be empty", START_LENGTH - 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(error: io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+impl Level {
+    // The board this level is played on, keeping the window size of `base`
+    pub fn config(&self, base: &GameConfig) -> GameConfig {
+        base.with_cells(self.width, self.height)
+    }
+}
+
+pub fn load_level(path: impl AsRef<Path>) -> Result<Level, LevelError> {
+    parse_level(&fs::read_to_string(path)?)
+}
+
+pub fn parse_level(text: &str) -> Result<Level, LevelError> {
+    let rows: Vec<&str> = text.lines().map(str::trim_end).filter(|row| !row.is_empty()).collect();
+    let width = rows.first().ok_or(LevelError::Empty)?.chars().count();
+    
+    let mut obstacles = Vec::new();
+    let mut snake_start = None;
+    let mut snake_direction = Direction::Right;
+    let mut food = None;
+    for (y, row) in rows.iter().enumerate() {
+        let line = y + 1;
+        if row.chars().count() != width {
+            return Err(LevelError::UnevenRow { line });
+        }
+        
+        for (x, tile) in row.chars().enumerate() {
+            let position = Position::new(x as i32, y as i32);
+            let slot = match tile {
+                '.' => continue,
+                '#' => {
+                    obstacles.push(position);
+                    continue;
+                }
+                'S' | '>' | '<' | '^' | 'v' => {
+                    snake_direction = match tile {
+                        '<' => Direction::Left,
+                        '^' => Direction::Up,
+                        'v' => Direction::Down,
+                        _ => Direction::Right,
+                    };
+                    &mut snake_start
+                }
+                'F' => &mut food,
+                _ => return Err(LevelError::UnknownTile { line, column: x + 1, tile }),
+            };
+            if slot.replace(position).is_some() {
+                return Err(LevelError::DuplicateTile { line, column: x + 1, tile });
+            }
+        }
+    }
+    
+    let snake_start = snake_start.ok_or(LevelError::MissingTile('S'))?;
+    let food = food.ok_or(LevelError::MissingTile('F'))?;
+    let body_is_clear = Snake::start_cells(snake_start, START_LENGTH, snake_direction).skip(1).all(|cell| {
+        let on_board = cell.x >= 0 && cell.y >= 0 && cell.x < width as i32 && cell.y < rows.len() as i32;
+        on_board && !obstacles.contains(&cell) && cell != food
+    });
+    if !body_is_clear {
+        return Err(LevelError::BlockedSnakeStart);
+    }
+    
+    Ok(Level {
+        width: width as i32,
+        height: rows.len() as i32,
+        obstacles,
+        snake_start,
+        snake_direction,
+        food,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_walls_start_and_food() {
+        let level = parse_level("#####\n#..S#\n#F..#\n#####\n").unwrap();
+        
+        assert_eq!((level.width, level.height), (5, 4));
+        assert_eq!(level.snake_start, Position::new(3, 1));
+        assert_eq!(level.snake_direction, Direction::Right);
+        assert_eq!(level.food, Position::new(1, 2));
+        assert_eq!(level.obstacles.len(), 14);
+    }
+    
+    #[test]
+    fn the_head_tile_sets_which_way_the_snake_starts() {
+        let level = parse_level("..^\n...\nF..").unwrap();
+        assert_eq!(level.snake_direction, Direction::Up);
+        assert_eq!(level.snake_start, Position::new(2, 0));
+        
+        // Facing up, the body hangs down from the head, so it needs the cells below clear
+        assert!(matches!(parse_level("..^\n..#\nF.."), Err(LevelError::BlockedSnakeStart)));
+        assert!(matches!(parse_level("F.v\n...\n..."), Err(LevelError::BlockedSnakeStart)));
+        assert!(matches!(parse_level("..<F"), Err(LevelError::BlockedSnakeStart)));
+    }
+    
+    #[test]
+    fn malformed_levels_are_rejected() {
+        assert!(matches!(parse_level(""), Err(LevelError::Empty)));
+        assert!(matches!(parse_level("..S\n.F"), Err(LevelError::UnevenRow { line: 2 })));
+        assert!(matches!(
+            parse_level("..S\n.X."),
+            Err(LevelError::UnknownTile { line: 2, column: 2, tile: 'X' })
+        ));
+        assert!(matches!(parse_level("..S\n..S\n.F."), Err(LevelError::DuplicateTile { tile: 'S', .. })));
+        assert!(matches!(parse_level("..S"), Err(LevelError::MissingTile('F'))));
+        assert!(matches!(parse_level("#.S\nF.."), Err(LevelError::BlockedSnakeStart)));
+    }
+    
+    #[test]
+    fn bundled_levels_parse() {
+        assert!(parse_level(include_str!("../levels/cross.txt")).is_ok());
+    }
+}
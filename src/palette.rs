@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+
+// Every color used to draw the board, so the whole look can be swapped without touching gameplay
+#[derive(Clone, Copy)]
+pub struct Palette {
+    pub snake: Color,
+    pub snake_head: Color,
+    pub second_snake: Color,
+    pub second_snake_head: Color,
+    pub food: Color,
+    pub bonus_food: Color,
+    pub poison_food: Color,
+    pub focus_food: Color,
+    pub golden_food: Color,
+    pub big_head_food: Color,
+    pub freeze_food: Color,
+    pub obstacle: Color,
+    pub arena_wall: Color,
+    // Frame around the board edge
+    pub border: Color,
+    pub portal: Color,
+    pub background: Color,
+    pub grass: Color,
+    pub grid: Color,
+    pub slow_motion_tint: Color,
+    pub freeze_tint: Color,
+    pub danger: Color,
+    pub text: Color,
+    pub highlight: Color,
+}
+
+impl Palette {
+    pub const CLASSIC: Palette = Palette {
+        snake: DARKGREEN,
+        snake_head: Color::new(0.0, 0.33, 0.1, 1.0),
+        second_snake: DARKBLUE,
+        second_snake_head: Color::new(0.0, 0.2, 0.45, 1.0),
+        food: RED,
+        bonus_food: GOLD,
+        poison_food: PURPLE,
+        focus_food: SKYBLUE,
+        golden_food: YELLOW,
+        big_head_food: ORANGE,
+        freeze_food: Color::new(0.7, 0.93, 1.0, 1.0),
+        obstacle: GRAY,
+        arena_wall: Color::new(0.4, 0.26, 0.13, 1.0),
+        border: Color::new(0.2, 0.35, 0.05, 1.0),
+        portal: MAGENTA,
+        background: Color::new(175.0 / 255.0, 215.0 / 255.0, 70.0 / 255.0, 1.0),
+        grass: Color::new(167.0 / 255.0, 209.0 / 255.0, 61.0 / 255.0, 1.0),
+        grid: Color::new(0.0, 0.0, 0.0, 0.25),
+        slow_motion_tint: Color::new(0.2, 0.4, 1.0, 0.12),
+        freeze_tint: Color::new(0.75, 0.95, 1.0, 0.15),
+        danger: Color::new(0.9, 0.0, 0.0, 0.35),
+        text: BLACK,
+        highlight: WHITE,
+    };
+    
+    // Blue/orange/yellow from the Okabe-Ito set stay distinct for common kinds of color blindness
+    pub const HIGH_CONTRAST: Palette = Palette {
+        snake: Color::new(0.0, 114.0 / 255.0, 178.0 / 255.0, 1.0),
+        snake_head: Color::new(86.0 / 255.0, 180.0 / 255.0, 233.0 / 255.0, 1.0),
+        second_snake: Color::new(204.0 / 255.0, 121.0 / 255.0, 167.0 / 255.0, 1.0),
+        second_snake_head: Color::new(230.0 / 255.0, 170.0 / 255.0, 205.0 / 255.0, 1.0),
+        food: Color::new(230.0 / 255.0, 159.0 / 255.0, 0.0, 1.0),
+        bonus_food: Color::new(240.0 / 255.0, 228.0 / 255.0, 66.0 / 255.0, 1.0),
+        poison_food: Color::new(0.6, 0.3, 0.9, 1.0),
+        focus_food: WHITE,
+        golden_food: Color::new(1.0, 0.84, 0.0, 1.0),
+        big_head_food: Color::new(213.0 / 255.0, 94.0 / 255.0, 0.0, 1.0),
+        freeze_food: Color::new(0.8, 0.95, 1.0, 1.0),
+        obstacle: Color::new(0.85, 0.85, 0.85, 1.0),
+        arena_wall: Color::new(213.0 / 255.0, 94.0 / 255.0, 0.0, 1.0),
+        border: Color::new(0.6, 0.6, 0.6, 1.0),
+        portal: Color::new(0.0, 158.0 / 255.0, 115.0 / 255.0, 1.0),
+        background: Color::new(0.1, 0.1, 0.1, 1.0),
+        grass: Color::new(0.16, 0.16, 0.16, 1.0),
+        grid: Color::new(1.0, 1.0, 1.0, 0.25),
+        slow_motion_tint: Color::new(86.0 / 255.0, 180.0 / 255.0, 233.0 / 255.0, 0.15),
+        freeze_tint: Color::new(0.8, 0.95, 1.0, 0.12),
+        danger: Color::new(213.0 / 255.0, 94.0 / 255.0, 0.0, 0.45),
+        text: WHITE,
+        highlight: Color::new(240.0 / 255.0, 228.0 / 255.0, 66.0 / 255.0, 1.0),
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaletteChoice {
+    Classic,
+    HighContrast,
+}
+
+impl PaletteChoice {
+    pub const ALL: [PaletteChoice; 2] = [PaletteChoice::Classic, PaletteChoice::HighContrast];
+    
+    pub fn colors(self) -> Palette {
+        match self {
+            PaletteChoice::Classic => Palette::CLASSIC,
+            PaletteChoice::HighContrast => Palette::HIGH_CONTRAST,
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            PaletteChoice::Classic => "Classic",
+            PaletteChoice::HighContrast => "High contrast",
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use macroquad::prelude::*;
+use ::rand::prelude::*;
+
+use crate::types::{Position, Viewport};
+
+const BURST_SIZE: usize = 12;
+const PARTICLE_LIFETIME: f32 = 0.5; // Seconds
+const PARTICLE_SPEED: f32 = 4.0; // Cells per second
+const PARTICLE_SIZE: f32 = 0.2; // Fraction of a cell
+
+// A speck thrown off when food is eaten; its position is in fractional cells so it can drift between them
+pub struct Particle {
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    age: f32,
+    color: Color,
+}
+
+impl Particle {
+    // A handful of particles flying out in every direction from the middle of `cell`.
+    // Purely cosmetic, so they don't touch the seeded food rng and replays stay intact
+    pub fn burst(cell: Position, color: Color) -> impl Iterator<Item = Particle> {
+        let mut rng = thread_rng();
+        (0..BURST_SIZE).map(move |_| {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = PARTICLE_SPEED * rng.gen_range(0.5..1.0);
+            Particle {
+                x: cell.x as f32 + 0.5,
+                y: cell.y as f32 + 0.5,
+                velocity_x: angle.cos() * speed,
+                velocity_y: angle.sin() * speed,
+                age: 0.0,
+                color,
+            }
+        })
+    }
+    
+    // Move and age the particle by `dt` seconds; returns false once it has faded out
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.x += self.velocity_x * dt;
+        self.y += self.velocity_y * dt;
+        self.age += dt;
+        self.age < PARTICLE_LIFETIME
+    }
+    
+    pub fn draw(&self, viewport: &Viewport) {
+        let fade = 1.0 - self.age / PARTICLE_LIFETIME;
+        let size = PARTICLE_SIZE * viewport.cell_width.min(viewport.cell_height);
+        let (x, y) = viewport.to_screen(self.x, self.y);
+        let color = Color {
+            a: self.color.a * fade,
+            ..self.color
+        };
+        draw_rectangle(x - size / 2.0, y - size / 2.0, size, size, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn particles_expire_after_their_lifetime() {
+        let mut particles: Vec<Particle> = Particle::burst(Position::new(3, 4), WHITE).collect();
+        assert_eq!(particles.len(), BURST_SIZE);
+        
+        particles.retain_mut(|particle| particle.update(PARTICLE_LIFETIME / 2.0));
+        assert_eq!(particles.len(), BURST_SIZE);
+        particles.retain_mut(|particle| particle.update(PARTICLE_LIFETIME / 2.0));
+        assert!(particles.is_empty());
+    }
+}
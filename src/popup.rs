@@ -0,0 +1,70 @@
+use macroquad::prelude::*;
+
+use crate::text;
+use crate::types::{Position, Viewport};
+
+const POPUP_LIFETIME: f64 = 0.8; // Seconds
+const POPUP_RISE: f32 = 1.5; // Cells over its lifetime
+const POPUP_FONT_SIZE: f32 = 0.9; // Fraction of a cell
+
+// The points a meal was worth, floating up off the cell it was eaten on and fading as it goes
+pub struct ScorePopup {
+    position: Position,
+    text: String,
+    spawned_at: f64,
+    lifetime: f64,
+}
+
+impl ScorePopup {
+    // `combo` is the streak before this meal, shown the same way as the HUD's combo counter
+    pub fn new(position: Position, points: u32, combo: u32, now: f64) -> Self {
+        let text = match combo {
+            0 => format!("+{points}"),
+            _ => format!("+{points} x{}", combo + 1),
+        };
+        Self {
+            position,
+            text,
+            spawned_at: now,
+            lifetime: POPUP_LIFETIME,
+        }
+    }
+    
+    pub fn is_expired(&self, now: f64) -> bool {
+        now - self.spawned_at >= self.lifetime
+    }
+    
+    // Keeps it from fading while the game is paused
+    pub fn delay(&mut self, seconds: f64) {
+        self.spawned_at += seconds;
+    }
+    
+    pub fn draw(&self, viewport: &Viewport, now: f64, color: Color) {
+        let age = ((now - self.spawned_at) / self.lifetime).clamp(0.0, 1.0) as f32;
+        let font_size = POPUP_FONT_SIZE * viewport.cell_height;
+        let width = text::measure(&self.text, font_size).width;
+        let (x, y) = viewport.to_screen(self.position.x as f32 + 0.5, self.position.y as f32 - age * POPUP_RISE);
+        let color = Color {
+            a: color.a * (1.0 - age),
+            ..color
+        };
+        text::draw(&self.text, x - width / 2.0, y, font_size, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn popups_show_the_combo_and_expire_after_their_lifetime() {
+        assert_eq!(ScorePopup::new(Position::new(1, 1), 1, 0, 0.0).text, "+1");
+        let mut popup = ScorePopup::new(Position::new(1, 1), 5, 2, 10.0);
+        assert_eq!(popup.text, "+5 x3");
+        
+        assert!(!popup.is_expired(10.0 + POPUP_LIFETIME / 2.0));
+        popup.delay(1.0);
+        assert!(!popup.is_expired(10.0 + POPUP_LIFETIME));
+        assert!(popup.is_expired(11.0 + POPUP_LIFETIME));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::snake::Direction;
+
+// Everything needed to reproduce a run: the food seed plus every event keyed by the tick it preceded
+#[derive(Clone, Default)]
+pub struct Replay {
+    pub seed: u64,
+    pub inputs: Vec<(u64, Direction)>,
+    // Which food item expired, by its index on the board
+    pub food_expirations: Vec<(u64, usize)>,
+    pub arena_shrinks: Vec<u64>,
+    // Ticks a survival wave dropped a new obstacle before
+    pub waves: Vec<u64>,
+    // The tick tail passthrough wore off on, if it was granted at all
+    pub passthrough_ended: Option<u64>,
+    // The tick a time attack run ran out of time on
+    pub timed_out: Option<u64>,
+    // Ticks player one cut off their tail before
+    pub sacrifices: Vec<u64>,
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+    
+    pub fn record_input(&mut self, tick: u64, direction: Direction) {
+        self.inputs.push((tick, direction));
+    }
+    
+    pub fn record_food_expiration(&mut self, tick: u64, index: usize) {
+        self.food_expirations.push((tick, index));
+    }
+    
+    pub fn record_arena_shrink(&mut self, tick: u64) {
+        self.arena_shrinks.push(tick);
+    }
+    
+    pub fn record_wave(&mut self, tick: u64) {
+        self.waves.push(tick);
+    }
+    
+    pub fn record_passthrough_end(&mut self, tick: u64) {
+        self.passthrough_ended = Some(tick);
+    }
+    
+    pub fn record_time_out(&mut self, tick: u64) {
+        self.timed_out = Some(tick);
+    }
+    
+    pub fn record_sacrifice(&mut self, tick: u64) {
+        self.sacrifices.push(tick);
+    }
+    
+    // Events are recorded in tick order, so each tick's entries form a contiguous run
+    pub fn inputs_at(&self, tick: u64) -> impl Iterator<Item = Direction> + '_ {
+        let start = self.inputs.partition_point(|&(input_tick, _)| input_tick < tick);
+        self.inputs[start..]
+            .iter()
+            .take_while(move |&&(input_tick, _)| input_tick == tick)
+            .map(|&(_, direction)| direction)
+    }
+    
+    pub fn food_expirations_at(&self, tick: u64) -> impl Iterator<Item = usize> + '_ {
+        let start = self.food_expirations.partition_point(|&(expiry_tick, _)| expiry_tick < tick);
+        self.food_expirations[start..]
+            .iter()
+            .take_while(move |&&(expiry_tick, _)| expiry_tick == tick)
+            .map(|&(_, index)| index)
+    }
+    
+    pub fn arena_shrinks_at(&self, tick: u64) -> bool {
+        self.arena_shrinks.binary_search(&tick).is_ok()
+    }
+    
+    pub fn waves_at(&self, tick: u64) -> bool {
+        self.waves.binary_search(&tick).is_ok()
+    }
+    
+    pub fn passthrough_ends_at(&self, tick: u64) -> bool {
+        self.passthrough_ended == Some(tick)
+    }
+    
+    pub fn sacrifices_at(&self, tick: u64) -> bool {
+        self.sacrifices.binary_search(&tick).is_ok()
+    }
+    
+    pub fn times_out_at(&self, tick: u64) -> bool {
+        self.timed_out == Some(tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn inputs_are_looked_up_by_tick() {
+        let mut replay = Replay::new(7);
+        replay.record_input(3, Direction::Up);
+        replay.record_input(3, Direction::Left);
+        replay.record_input(9, Direction::Down);
+        
+        assert_eq!(replay.inputs_at(3).collect::<Vec<_>>(), [Direction::Up, Direction::Left]);
+        assert_eq!(replay.inputs_at(9).collect::<Vec<_>>(), [Direction::Down]);
+        assert_eq!(replay.inputs_at(4).count(), 0);
+    }
+}
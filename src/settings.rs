@@ -0,0 +1,693 @@
+use crate::palette::PaletteChoice;
+use crate::types::{GameConfig, WallMode};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl Speed {
+    pub const ALL: [Speed; 3] = [Speed::Slow, Speed::Normal, Speed::Fast];
+    
+    pub fn update_interval(self) -> f64 {
+        match self {
+            Speed::Slow => 0.2,
+            Speed::Normal => 0.15,
+            Speed::Fast => 0.1,
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            Speed::Slow => "Slow",
+            Speed::Normal => "Normal",
+            Speed::Fast => "Fast",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+// What a difficulty changes about a run
+pub struct DifficultyParams {
+    // Multiplies the chosen speed's update interval, so below 1.0 is faster
+    pub interval_scale: f64,
+    // Obstacle layout placed on the board, see `level_obstacles`
+    pub level: u32,
+    // Seconds at the start of a run during which the snake can pass through its own body
+    pub tail_passthrough_seconds: f64,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+    
+    pub fn params(self) -> DifficultyParams {
+        match self {
+            Difficulty::Easy => DifficultyParams {
+                interval_scale: 1.25,
+                level: 0,
+                tail_passthrough_seconds: 5.0,
+            },
+            Difficulty::Normal => DifficultyParams {
+                interval_scale: 1.0,
+                level: 1,
+                tail_passthrough_seconds: 0.0,
+            },
+            Difficulty::Hard => DifficultyParams {
+                interval_scale: 0.8,
+                level: 2,
+                tail_passthrough_seconds: 0.0,
+            },
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl BoardSize {
+    const ALL: [BoardSize; 3] = [BoardSize::Small, BoardSize::Medium, BoardSize::Large];
+    
+    // Cell size at the default window size; the board still stretches with the window
+    fn cell_size(self) -> f32 {
+        match self {
+            BoardSize::Small => 40.0,
+            BoardSize::Medium => 20.0,
+            BoardSize::Large => 10.0,
+        }
+    }
+    
+    pub fn config(self) -> GameConfig {
+        GameConfig::with_cell_size(self.cell_size())
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            BoardSize::Small => "Small",
+            BoardSize::Medium => "Medium",
+            BoardSize::Large => "Large",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameMode {
+    Classic,
+    // The board shrinks over time, walling off its outer ring
+    Arena,
+    // Score as much as possible before the clock runs out; food buys more time
+    TimeAttack,
+    // Food comes in clusters of neighbouring cells, and a new cluster only appears once one is cleared
+    Feast,
+    // Two foods that look nearly alike, one of them poison; eating either brings a new pair
+    Risk,
+    // A new obstacle lands on a random free cell every few seconds
+    Waves,
+}
+
+impl GameMode {
+    const ALL: [GameMode; MODE_COUNT] = [
+        GameMode::Classic,
+        GameMode::Arena,
+        GameMode::TimeAttack,
+        GameMode::Feast,
+        GameMode::Risk,
+        GameMode::Waves,
+    ];
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Arena => "Arena",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Feast => "Feast",
+            GameMode::Risk => "Risk",
+            GameMode::Waves => "Waves",
+        }
+    }
+    
+    fn index(self) -> usize {
+        GameMode::ALL.iter().position(|&mode| mode == self).unwrap_or(0)
+    }
+}
+
+const MODE_COUNT: usize = 6;
+
+// Length that wins a round, as a share of the board's cells
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WinTarget {
+    Endless,
+    Quarter,
+    Half,
+}
+
+impl WinTarget {
+    const ALL: [WinTarget; 3] = [WinTarget::Endless, WinTarget::Quarter, WinTarget::Half];
+    
+    pub fn board_fraction(self) -> Option<f64> {
+        match self {
+            WinTarget::Endless => None,
+            WinTarget::Quarter => Some(0.25),
+            WinTarget::Half => Some(0.5),
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            WinTarget::Endless => "Endless",
+            WinTarget::Quarter => "25% of board",
+            WinTarget::Half => "50% of board",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerCount {
+    One,
+    Two,
+}
+
+impl PlayerCount {
+    const ALL: [PlayerCount; 2] = [PlayerCount::One, PlayerCount::Two];
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            PlayerCount::One => "1",
+            PlayerCount::Two => "2",
+        }
+    }
+}
+
+// How the direction keys steer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlScheme {
+    // Each key turns the snake to face that way
+    Absolute,
+    // Left and right turn the snake a quarter about its heading; up and down do nothing
+    Relative,
+}
+
+impl ControlScheme {
+    const ALL: [ControlScheme; 2] = [ControlScheme::Absolute, ControlScheme::Relative];
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "Absolute",
+            ControlScheme::Relative => "Relative",
+        }
+    }
+}
+
+// How many food items can be on the board at once
+const FOOD_COUNTS: [usize; 5] = [1, 2, 3, 4, 5];
+// Seconds allowed without eating before the round ends, if there's a limit at all
+const STALL_LIMITS: [Option<u32>; 4] = [None, Some(30), Some(20), Some(10)];
+// Gap left around each snake segment, food and obstacle, as a fraction of a cell
+const SEGMENT_PADDINGS: [f32; 3] = [0.0, 0.06, 0.12];
+// Seconds new food takes to grow to full size
+const FOOD_GROW_TIMES: [f64; 3] = [0.0, 0.2, 0.4];
+// How loud sound effects play, from silent to full
+const VOLUMES: [f32; 6] = [0.0, 0.2, 0.4, 0.6, 0.8, 1.0];
+
+// Choices made on the settings screen, kept for the whole session
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub speed: Speed,
+    // Nudge the speed up or down depending on how quickly food is being eaten
+    pub adaptive_speed: bool,
+    pub board_size: BoardSize,
+    pub palette: PaletteChoice,
+    pub players: PlayerCount,
+    pub mode: GameMode,
+    // Seconds a time attack run starts with, and how many each food adds
+    pub time_budget: f64,
+    pub time_bonus: f64,
+    pub wall_mode: WallMode,
+    pub portals: bool,
+    pub food_count: usize,
+    // Food hops around the board instead of waiting to be eaten
+    pub runaway_food: bool,
+    // Adds big head food, which makes the snake two cells wide for a while
+    pub big_head_food: bool,
+    // Holding a direction key keeps steering that way instead of only turning on the press
+    pub hold_to_steer: bool,
+    pub control_scheme: ControlScheme,
+    // Going this many seconds without food ends the round; less is allowed as the snake grows
+    pub stall_limit: Option<u32>,
+    // More than zero draws cells apart from each other, for an LED look
+    pub segment_padding: f32,
+    // Zero makes new food appear at full size
+    pub food_grow_time: f64,
+    // Draw normal food with the apple image, when there is one
+    pub food_sprite: bool,
+    // Flash the screen edges when the next move would crash
+    pub danger_warning: bool,
+    pub win_target: WinTarget,
+    // Picking the poison in risk mode ends the round instead of costing length
+    pub wrong_pick_ends_run: bool,
+    // Which modes get short warp speed bursts at high scores, indexed like `GameMode::ALL`
+    pub warp_modes: [bool; MODE_COUNT],
+    // The game over screen wants the restart key twice, so a stray press doesn't throw away the results
+    pub confirm_restart: bool,
+    // Crashing rewinds the snakes a moment instead of ending the round, and nothing is recorded
+    pub practice: bool,
+    // Sound effect volume, and whether they're silenced without losing it
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            speed: Speed::Normal,
+            adaptive_speed: true,
+            board_size: BoardSize::Medium,
+            palette: PaletteChoice::Classic,
+            players: PlayerCount::One,
+            mode: GameMode::Classic,
+            time_budget: 60.0,
+            time_bonus: 3.0,
+            wall_mode: WallMode::Solid,
+            portals: false,
+            food_count: 1,
+            runaway_food: false,
+            big_head_food: false,
+            hold_to_steer: false,
+            control_scheme: ControlScheme::Absolute,
+            stall_limit: None,
+            segment_padding: 0.0,
+            food_grow_time: 0.2,
+            food_sprite: true,
+            danger_warning: true,
+            win_target: WinTarget::Endless,
+            wrong_pick_ends_run: true,
+            warp_modes: [false; MODE_COUNT],
+            confirm_restart: false,
+            practice: false,
+            volume: 0.4,
+            muted: false,
+        }
+    }
+}
+
+impl Settings {
+    // Tiny cells at top speed are too hard to follow
+    pub fn is_valid(&self) -> bool {
+        !(self.speed == Speed::Fast && self.board_size == BoardSize::Large)
+    }
+    
+    // Starting update interval before any food has been eaten
+    pub fn base_update_interval(&self) -> f64 {
+        self.speed.update_interval() * self.difficulty.params().interval_scale
+    }
+    
+    // One `key=value` line per setting, in the format of the settings file
+    pub fn to_config(self) -> String {
+        [
+            ("difficulty", self.difficulty.label().to_owned()),
+            ("speed", self.speed.label().to_owned()),
+            ("adaptive_speed", self.adaptive_speed.to_string()),
+            ("board_size", self.board_size.label().to_owned()),
+            ("palette", self.palette.label().to_owned()),
+            ("players", self.players.label().to_owned()),
+            ("mode", self.mode.label().to_owned()),
+            ("time_budget", self.time_budget.to_string()),
+            ("time_bonus", self.time_bonus.to_string()),
+            ("wall_mode", self.wall_mode.label().to_owned()),
+            ("portals", self.portals.to_string()),
+            ("food_count", self.food_count.to_string()),
+            ("runaway_food", self.runaway_food.to_string()),
+            ("big_head_food", self.big_head_food.to_string()),
+            ("hold_to_steer", self.hold_to_steer.to_string()),
+            ("control_scheme", self.control_scheme.label().to_owned()),
+            ("stall_limit", stall_limit_value(self.stall_limit)),
+            ("segment_padding", self.segment_padding.to_string()),
+            ("food_grow_time", self.food_grow_time.to_string()),
+            ("food_sprite", self.food_sprite.to_string()),
+            ("danger_warning", self.danger_warning.to_string()),
+            ("win_target", self.win_target.label().to_owned()),
+            ("wrong_pick_ends_run", self.wrong_pick_ends_run.to_string()),
+            ("warp_modes", self.warp_modes_value()),
+            ("confirm_restart", self.confirm_restart.to_string()),
+            ("practice", self.practice.to_string()),
+            ("volume", self.volume.to_string()),
+            ("muted", self.muted.to_string()),
+        ]
+        .map(|(key, value)| format!("{key}={value}\n"))
+        .concat()
+    }
+    
+    // Apply one line of the settings file; an unknown key or a bad value returns false and changes nothing
+    pub fn apply_config(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "difficulty" => set(&mut self.difficulty, by_label(&Difficulty::ALL, Difficulty::label, value)),
+            "speed" => set(&mut self.speed, by_label(&Speed::ALL, Speed::label, value)),
+            "adaptive_speed" => set(&mut self.adaptive_speed, value.parse().ok()),
+            "board_size" => set(&mut self.board_size, by_label(&BoardSize::ALL, BoardSize::label, value)),
+            "palette" => set(&mut self.palette, by_label(&PaletteChoice::ALL, PaletteChoice::label, value)),
+            "players" => set(&mut self.players, by_label(&PlayerCount::ALL, PlayerCount::label, value)),
+            "mode" => set(&mut self.mode, by_label(&GameMode::ALL, GameMode::label, value)),
+            "time_budget" => set(&mut self.time_budget, value.parse().ok().filter(|&seconds: &f64| seconds > 0.0)),
+            "time_bonus" => set(&mut self.time_bonus, value.parse().ok().filter(|&seconds: &f64| seconds >= 0.0)),
+            "wall_mode" => set(&mut self.wall_mode, by_label(&WallMode::ALL, WallMode::label, value)),
+            "portals" => set(&mut self.portals, value.parse().ok()),
+            "food_count" => set(&mut self.food_count, value.parse().ok().filter(|count| FOOD_COUNTS.contains(count))),
+            "runaway_food" => set(&mut self.runaway_food, value.parse().ok()),
+            "big_head_food" => set(&mut self.big_head_food, value.parse().ok()),
+            "hold_to_steer" => set(&mut self.hold_to_steer, value.parse().ok()),
+            "control_scheme" => set(
+                &mut self.control_scheme,
+                by_label(&ControlScheme::ALL, ControlScheme::label, value),
+            ),
+            "segment_padding" => set(
+                &mut self.segment_padding,
+                value.parse().ok().filter(|padding| SEGMENT_PADDINGS.contains(padding)),
+            ),
+            "food_grow_time" => set(
+                &mut self.food_grow_time,
+                value.parse().ok().filter(|seconds| FOOD_GROW_TIMES.contains(seconds)),
+            ),
+            "food_sprite" => set(&mut self.food_sprite, value.parse().ok()),
+            "stall_limit" => set(
+                &mut self.stall_limit,
+                STALL_LIMITS.into_iter().find(|&limit| stall_limit_value(limit) == value),
+            ),
+            "danger_warning" => set(&mut self.danger_warning, value.parse().ok()),
+            "win_target" => set(&mut self.win_target, by_label(&WinTarget::ALL, WinTarget::label, value)),
+            "wrong_pick_ends_run" => set(&mut self.wrong_pick_ends_run, value.parse().ok()),
+            "warp_modes" => set(&mut self.warp_modes, parse_warp_modes(value)),
+            "confirm_restart" => set(&mut self.confirm_restart, value.parse().ok()),
+            "practice" => set(&mut self.practice, value.parse().ok()),
+            "volume" => set(&mut self.volume, value.parse().ok().filter(|volume| VOLUMES.contains(volume))),
+            "muted" => set(&mut self.muted, value.parse().ok()),
+            _ => false,
+        }
+    }
+    
+    pub fn cycle_difficulty(&mut self, forward: bool) {
+        self.difficulty = cycle(&Difficulty::ALL, self.difficulty, forward);
+    }
+    
+    pub fn cycle_speed(&mut self, forward: bool) {
+        let start = self.speed;
+        loop {
+            self.speed = cycle(&Speed::ALL, self.speed, forward);
+            if self.is_valid() || self.speed == start {
+                break;
+            }
+        }
+    }
+    
+    pub fn cycle_board_size(&mut self, forward: bool) {
+        let start = self.board_size;
+        loop {
+            self.board_size = cycle(&BoardSize::ALL, self.board_size, forward);
+            if self.is_valid() || self.board_size == start {
+                break;
+            }
+        }
+    }
+    
+    pub fn cycle_palette(&mut self, forward: bool) {
+        self.palette = cycle(&PaletteChoice::ALL, self.palette, forward);
+    }
+    
+    pub fn cycle_players(&mut self, forward: bool) {
+        self.players = cycle(&PlayerCount::ALL, self.players, forward);
+    }
+    
+    pub fn cycle_mode(&mut self, forward: bool) {
+        self.mode = cycle(&GameMode::ALL, self.mode, forward);
+    }
+    
+    pub fn cycle_wall_mode(&mut self, forward: bool) {
+        self.wall_mode = cycle(&WallMode::ALL, self.wall_mode, forward);
+    }
+    
+    pub fn toggle_adaptive_speed(&mut self) {
+        self.adaptive_speed = !self.adaptive_speed;
+    }
+    
+    pub fn toggle_portals(&mut self) {
+        self.portals = !self.portals;
+    }
+    
+    pub fn cycle_food_count(&mut self, forward: bool) {
+        self.food_count = cycle(&FOOD_COUNTS, self.food_count, forward);
+    }
+    
+    pub fn toggle_runaway_food(&mut self) {
+        self.runaway_food = !self.runaway_food;
+    }
+    
+    pub fn toggle_big_head_food(&mut self) {
+        self.big_head_food = !self.big_head_food;
+    }
+    
+    pub fn toggle_hold_to_steer(&mut self) {
+        self.hold_to_steer = !self.hold_to_steer;
+    }
+    
+    pub fn cycle_control_scheme(&mut self, forward: bool) {
+        self.control_scheme = cycle(&ControlScheme::ALL, self.control_scheme, forward);
+    }
+    
+    pub fn cycle_stall_limit(&mut self, forward: bool) {
+        self.stall_limit = cycle(&STALL_LIMITS, self.stall_limit, forward);
+    }
+    
+    pub fn cycle_segment_padding(&mut self, forward: bool) {
+        self.segment_padding = cycle(&SEGMENT_PADDINGS, self.segment_padding, forward);
+    }
+    
+    pub fn segment_padding_label(self) -> &'static str {
+        match SEGMENT_PADDINGS.iter().position(|&padding| padding == self.segment_padding) {
+            Some(1) => "Thin",
+            Some(2) => "Wide",
+            _ => "None",
+        }
+    }
+    
+    pub fn cycle_food_grow_time(&mut self, forward: bool) {
+        self.food_grow_time = cycle(&FOOD_GROW_TIMES, self.food_grow_time, forward);
+    }
+    
+    pub fn toggle_food_sprite(&mut self) {
+        self.food_sprite = !self.food_sprite;
+    }
+    
+    pub fn food_grow_time_label(self) -> &'static str {
+        match FOOD_GROW_TIMES.iter().position(|&seconds| seconds == self.food_grow_time) {
+            Some(1) => "Quick",
+            Some(2) => "Slow",
+            _ => "Off",
+        }
+    }
+    
+    pub fn stall_limit_label(self) -> String {
+        match self.stall_limit {
+            Some(seconds) => format!("{seconds}s"),
+            None => "Off".to_owned(),
+        }
+    }
+    
+    pub fn toggle_danger_warning(&mut self) {
+        self.danger_warning = !self.danger_warning;
+    }
+    
+    pub fn cycle_win_target(&mut self, forward: bool) {
+        self.win_target = cycle(&WinTarget::ALL, self.win_target, forward);
+    }
+    
+    pub fn toggle_wrong_pick_ends_run(&mut self) {
+        self.wrong_pick_ends_run = !self.wrong_pick_ends_run;
+    }
+    
+    // Whether the mode being played has warp speed switched on
+    pub fn warp(self) -> bool {
+        self.warp_modes[self.mode.index()]
+    }
+    
+    pub fn toggle_warp(&mut self) {
+        let index = self.mode.index();
+        self.warp_modes[index] = !self.warp_modes[index];
+    }
+    
+    fn warp_modes_value(self) -> String {
+        let labels: Vec<&str> = GameMode::ALL
+            .iter()
+            .filter(|mode| self.warp_modes[mode.index()])
+            .map(|mode| mode.label())
+            .collect();
+        labels.join(",")
+    }
+    
+    pub fn toggle_confirm_restart(&mut self) {
+        self.confirm_restart = !self.confirm_restart;
+    }
+    
+    pub fn toggle_practice(&mut self) {
+        self.practice = !self.practice;
+    }
+    
+    pub fn cycle_volume(&mut self, forward: bool) {
+        self.volume = cycle(&VOLUMES, self.volume, forward);
+    }
+    
+    pub fn toggle_muted(&mut self) {
+        self.muted = !self.muted;
+    }
+    
+    // What sound effects actually play at
+    pub fn sound_volume(self) -> f32 {
+        match self.muted {
+            true => 0.0,
+            false => self.volume,
+        }
+    }
+    
+    pub fn volume_label(self) -> String {
+        let percent = (self.volume * 100.0).round();
+        match self.muted {
+            true => format!("{percent}% (muted)"),
+            false => format!("{percent}%"),
+        }
+    }
+}
+
+fn set<T>(slot: &mut T, value: Option<T>) -> bool {
+    match value {
+        Some(value) => {
+            *slot = value;
+            true
+        }
+        None => false,
+    }
+}
+
+// A comma separated list of mode labels; one that isn't a mode spoils the lot
+fn parse_warp_modes(value: &str) -> Option<[bool; MODE_COUNT]> {
+    let mut modes = [false; MODE_COUNT];
+    for label in value.split(',').filter(|label| !label.is_empty()) {
+        modes[by_label(&GameMode::ALL, GameMode::label, label)?.index()] = true;
+    }
+    Some(modes)
+}
+
+fn stall_limit_value(limit: Option<u32>) -> String {
+    match limit {
+        Some(seconds) => seconds.to_string(),
+        None => "off".to_owned(),
+    }
+}
+
+fn by_label<T: Copy>(values: &[T], label: fn(T) -> &'static str, wanted: &str) -> Option<T> {
+    values.iter().copied().find(|&value| label(value) == wanted)
+}
+
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, forward: bool) -> T {
+    let index = values.iter().position(|&value| value == current).unwrap_or(0);
+    let next = if forward {
+        (index + 1) % values.len()
+    } else {
+        (index + values.len() - 1) % values.len()
+    };
+    values[next]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn settings_survive_a_round_trip_through_the_config_format() {
+        let settings = Settings {
+            difficulty: Difficulty::Hard,
+            palette: PaletteChoice::HighContrast,
+            wall_mode: WallMode::OneFreeWrap,
+            food_count: 3,
+            time_bonus: 2.5,
+            stall_limit: Some(20),
+            segment_padding: 0.12,
+            food_grow_time: 0.4,
+            control_scheme: ControlScheme::Relative,
+            warp_modes: [true, false, false, true, false, false],
+            practice: true,
+            volume: 0.8,
+            muted: true,
+            ..Settings::default()
+        };
+        
+        let mut loaded = Settings::default();
+        for line in settings.to_config().lines() {
+            let (key, value) = line.split_once('=').unwrap();
+            assert!(loaded.apply_config(key, value), "{line}");
+        }
+        assert_eq!(loaded, settings);
+        
+        assert!(!loaded.apply_config("difficulty", "Impossible"));
+        assert!(!loaded.apply_config("food_count", "9"));
+        assert!(!loaded.apply_config("volume", "0.5"));
+        assert!(!loaded.apply_config("colour", "Classic"));
+        assert!(!loaded.apply_config("warp_modes", "Classic,Chess"));
+        assert_eq!(loaded, settings);
+    }
+    
+    #[test]
+    fn cycling_wraps_around() {
+        let mut settings = Settings::default();
+        settings.cycle_speed(false);
+        assert_eq!(settings.speed, Speed::Slow);
+        settings.cycle_speed(false);
+        assert_eq!(settings.speed, Speed::Fast);
+    }
+    
+    #[test]
+    fn cycling_skips_invalid_combinations() {
+        let mut settings = Settings {
+            speed: Speed::Fast,
+            board_size: BoardSize::Medium,
+            ..Default::default()
+        };
+        
+        settings.cycle_board_size(true);
+        assert_eq!(settings.board_size, BoardSize::Small);
+        
+        settings.board_size = BoardSize::Large;
+        settings.speed = Speed::Normal;
+        settings.cycle_speed(true);
+        assert_eq!(settings.speed, Speed::Slow);
+        assert!(settings.is_valid());
+    }
+    
+    #[test]
+    fn harder_difficulties_start_faster_with_more_obstacles() {
+        let easy = Settings { difficulty: Difficulty::Easy, ..Default::default() };
+        let hard = Settings { difficulty: Difficulty::Hard, ..Default::default() };
+        
+        assert!(hard.base_update_interval() < easy.base_update_interval());
+        assert_eq!(easy.difficulty.params().level, 0);
+        assert!(hard.difficulty.params().level > easy.difficulty.params().level);
+    }
+}
@@ -0,0 +1,706 @@
+use macroquad::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::types::{GameConfig, Position, Viewport, WallMode};
+
+const MAX_BUFFERED_TURNS: usize = 2;
+const EYE_RADIUS: f32 = 0.12; // Fraction of a cell
+const PUPIL_RADIUS: f32 = 0.06;
+const SEGMENT_RADIUS: f32 = 0.45; // Fraction of a cell, for rounded segments
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SegmentStyle {
+    // One square per cell, the classic look
+    Square,
+    // Circles at each cell joined by thick lines, so the body reads as one continuous tube
+    Rounded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+    
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+    
+    // One cell's move in this direction, with y growing down the board
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+    
+    // A quarter turn, as seen from above the board
+    pub fn rotated(self, clockwise: bool) -> Self {
+        let turned = match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        };
+        match clockwise {
+            true => turned,
+            false => turned.opposite(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Snake {
+    pub body: VecDeque<Position>,
+    // How many segments sit on each cell, kept in step with `body` so collision checks don't scan it.
+    // Counts rather than a set because tail passthrough lets the body overlap itself
+    occupancy: HashMap<Position, usize>,
+    // Body as it was before the last move, used to interpolate drawing between ticks
+    previous_body: VecDeque<Position>,
+    // The way the last move went; only `update` changes it, and `apply_pending_direction` checks each
+    // buffered turn against it, so a turn is never made back into the neck
+    direction: Direction,
+    pending_directions: VecDeque<Direction>,
+    grow_next: bool,
+    // Side in cells of the square each segment covers, running right and down from the segment's cell;
+    // only more than one while a big head lasts
+    footprint: i32,
+    // Moves left before the footprint shrinks back to one cell
+    footprint_ticks: u32,
+    pub alive: bool,
+}
+
+impl Snake {
+    // A snake facing `direction` with its head at `head` and the rest of its `length` trailing straight
+    // out behind it; it always has at least a head
+    pub fn new(head: Position, length: usize, direction: Direction) -> Self {
+        let body: VecDeque<Position> = Self::start_cells(head, length, direction).collect();
+        
+        let mut snake = Self {
+            previous_body: body.clone(),
+            body: VecDeque::new(),
+            occupancy: HashMap::new(),
+            direction,
+            pending_directions: VecDeque::new(),
+            grow_next: false,
+            footprint: 1,
+            footprint_ticks: 0,
+            alive: true,
+        };
+        for segment in body {
+            snake.body.push_back(segment);
+            snake.occupy(segment);
+        }
+        snake
+    }
+    
+    // The cells `new` lays the body on, head first, so a level can check they're clear beforehand
+    pub fn start_cells(head: Position, length: usize, direction: Direction) -> impl Iterator<Item = Position> {
+        let (dx, dy) = direction.offset();
+        (0..length.max(1) as i32).map(move |offset| Position::new(head.x - dx * offset, head.y - dy * offset))
+    }
+    
+    pub fn head(&self) -> Position {
+        *self.body.front().unwrap()
+    }
+    
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+    
+    // The direction the snake will be going once every buffered turn is made
+    pub fn heading(&self) -> Direction {
+        self.pending_directions.back().copied().unwrap_or(self.direction)
+    }
+    
+    // `portals` are linked both ways: entering either end moves the head onto the other
+    pub fn update(&mut self, config: &GameConfig, wall_mode: WallMode, portals: &[(Position, Position)]) {
+        self.apply_pending_direction();
+        let new_head = self.step(self.direction, config, wall_mode, portals);
+        
+        self.previous_body.clone_from(&self.body);
+        self.body.push_front(new_head);
+        self.occupy(new_head);
+        
+        if !self.grow_next {
+            if let Some(tail) = self.body.pop_back() {
+                self.vacate(tail);
+            }
+        } else {
+            self.grow_next = false;
+        }
+        
+        if self.footprint_ticks > 0 {
+            self.footprint_ticks -= 1;
+            if self.footprint_ticks == 0 {
+                self.footprint = 1;
+            }
+        }
+    }
+    
+    // Where the head will be after the next update, taking any buffered turn into account
+    pub fn next_head(&self, config: &GameConfig, wall_mode: WallMode, portals: &[(Position, Position)]) -> Position {
+        let direction = match self.pending_directions.front() {
+            Some(&pending) if pending != self.direction.opposite() => pending,
+            _ => self.direction,
+        };
+        self.step(direction, config, wall_mode, portals)
+    }
+    
+    // Where a move in `direction` would take the head
+    pub fn step(&self, direction: Direction, config: &GameConfig, wall_mode: WallMode, portals: &[(Position, Position)]) -> Position {
+        let head = self.head();
+        let (dx, dy) = direction.offset();
+        let mut new_head = Position::new(head.x + dx, head.y + dy);
+        
+        if wall_mode == WallMode::Wrap {
+            new_head.x = new_head.x.rem_euclid(config.cell_number_x);
+            new_head.y = new_head.y.rem_euclid(config.cell_number_y);
+        }
+        
+        let exit = portals.iter().find_map(|&(a, b)| {
+            if new_head == a {
+                Some(b)
+            } else if new_head == b {
+                Some(a)
+            } else {
+                None
+            }
+        });
+        // An exit on the current head would fold the snake onto its own neck, so that portal stays shut
+        match exit.filter(|&exit| exit != head) {
+            Some(exit) => exit,
+            None => new_head,
+        }
+    }
+    
+    // Bring a head that left the board back in on the opposite side
+    pub fn wrap_head(&mut self, config: &GameConfig) {
+        let head = self.head();
+        let wrapped = Position::new(head.x.rem_euclid(config.cell_number_x), head.y.rem_euclid(config.cell_number_y));
+        self.body[0] = wrapped;
+        self.vacate(head);
+        self.occupy(wrapped);
+    }
+    
+    // Whether the tail will still be in place after the next update
+    pub fn is_growing(&self) -> bool {
+        self.grow_next
+    }
+    
+    pub fn grow(&mut self) {
+        self.grow_next = true;
+    }
+    
+    // Cover `size` by `size` cells per segment for the next `ticks` moves
+    pub fn enlarge(&mut self, size: i32, ticks: u32) {
+        self.footprint = size;
+        self.footprint_ticks = ticks;
+    }
+    
+    // Every cell the segment at `segment` covers; the part of a big square hanging off the edge is
+    // wrapped onto the board, which only matters in wrap mode since walls stop it anywhere else
+    pub fn covered_cells(&self, segment: Position, config: &GameConfig) -> impl Iterator<Item = Position> {
+        let size = self.footprint;
+        let (width, height) = (config.cell_number_x, config.cell_number_y);
+        (0..size * size).map(move |index| match (index % size, index / size) {
+            (0, 0) => segment,
+            (dx, dy) => Position::new((segment.x + dx).rem_euclid(width), (segment.y + dy).rem_euclid(height)),
+        })
+    }
+    
+    pub fn head_covers(&self, cell: Position, config: &GameConfig) -> bool {
+        self.covered_cells(self.head(), config).any(|covered| covered == cell)
+    }
+    
+    // Whether any part of the body covers `cell`
+    pub fn covers(&self, cell: Position, config: &GameConfig) -> bool {
+        if self.footprint == 1 {
+            return self.occupancy.contains_key(&cell);
+        }
+        self.body
+            .iter()
+            .any(|&segment| self.covered_cells(segment, config).any(|covered| covered == cell))
+    }
+    
+    // Forget turns queued but not made yet
+    pub fn clear_turns(&mut self) {
+        self.pending_directions.clear();
+    }
+    
+    // Drop `segments` from the tail; returns false, leaving the body alone, if that would leave no segments
+    pub fn shrink(&mut self, segments: usize) -> bool {
+        if self.body.len() <= segments {
+            return false;
+        }
+        for _ in 0..segments {
+            if let Some(tail) = self.body.pop_back() {
+                self.vacate(tail);
+            }
+        }
+        true
+    }
+    
+    fn occupy(&mut self, cell: Position) {
+        *self.occupancy.entry(cell).or_insert(0) += 1;
+    }
+    
+    fn vacate(&mut self, cell: Position) {
+        if let Some(count) = self.occupancy.get_mut(&cell) {
+            *count -= 1;
+            if *count == 0 {
+                self.occupancy.remove(&cell);
+            }
+        }
+    }
+    
+    pub fn change_direction(&mut self, new_direction: Direction) {
+        // Buffer turns so several presses within one tick are applied on consecutive ticks. Each turn is
+        // checked against the one queued before it, since that's the direction it will be turning from
+        if new_direction == self.heading().opposite() {
+            return;
+        }
+        if self.pending_directions.len() < MAX_BUFFERED_TURNS {
+            self.pending_directions.push_back(new_direction);
+        }
+    }
+    
+    fn apply_pending_direction(&mut self) {
+        if let Some(new_direction) = self.pending_directions.pop_front() {
+            if new_direction != self.direction.opposite() {
+                self.direction = new_direction;
+            }
+        }
+    }
+    
+    pub fn check_wall_collision(&self, config: &GameConfig, wall_mode: WallMode) -> bool {
+        if wall_mode == WallMode::Wrap {
+            return false;
+        }
+        
+        let head = self.head();
+        let far = self.footprint - 1;
+        head.x < 0 || head.x + far >= config.cell_number_x || head.y < 0 || head.y + far >= config.cell_number_y
+    }
+    
+    pub fn check_self_collision(&self, config: &GameConfig) -> bool {
+        if self.footprint == 1 {
+            return self.occupancy.get(&self.head()).is_some_and(|&count| count > 1);
+        }
+        
+        // Squares that big always overlap along the neck, so only segments far enough back to have
+        // curled round into the head count
+        let head: HashSet<Position> = self.covered_cells(self.head(), config).collect();
+        self.body
+            .iter()
+            .skip(2 * self.footprint as usize - 1)
+            .any(|&segment| self.covered_cells(segment, config).any(|cell| head.contains(&cell)))
+    }
+    
+    // Where segment `index` is drawn, in fractional cells, `progress` of the way through its move
+    pub fn draw_position(&self, index: usize, progress: f32) -> (f32, f32) {
+        let segment = self.body[index];
+        // A segment kept by growth has no previous position and simply stays put
+        let from = self.previous_body.get(index).copied().unwrap_or(segment);
+        let (dx, dy) = (segment.x - from.x, segment.y - from.y);
+        
+        // Don't slide across the whole board when wrapping around an edge
+        if dx.abs() + dy.abs() > 1 {
+            (segment.x as f32, segment.y as f32)
+        } else {
+            (from.x as f32 + dx as f32 * progress, from.y as f32 + dy as f32 * progress)
+        }
+    }
+    
+    // `progress` is how far through the current tick we are, from 0.0 to 1.0; `padding` is the gap left
+    // around each segment, in cells
+    pub fn draw(
+        &self,
+        viewport: &Viewport,
+        progress: f32,
+        body_color: Color,
+        head_color: Color,
+        style: SegmentStyle,
+        padding: f32,
+    ) {
+        let cells: Vec<(f32, f32)> = (0..self.body.len()).map(|index| self.draw_position(index, progress)).collect();
+        
+        let size = self.footprint as f32;
+        match style {
+            SegmentStyle::Square => {
+                for (index, &(x, y)) in cells.iter().enumerate() {
+                    let color = match index {
+                        0 => head_color,
+                        _ => body_color,
+                    };
+                    viewport.draw_inset_cell(x, y, size, padding, color);
+                }
+            }
+            SegmentStyle::Rounded => self.draw_rounded(viewport, &cells, body_color, head_color, padding),
+        }
+        
+        let (head_x, head_y) = cells[0];
+        self.draw_eyes(viewport, head_x, head_y);
+    }
+    
+    fn draw_rounded(&self, viewport: &Viewport, cells: &[(f32, f32)], body_color: Color, head_color: Color, padding: f32) {
+        let size = self.footprint as f32;
+        let radius = (SEGMENT_RADIUS * size - padding).max(0.0) * viewport.cell_width.min(viewport.cell_height);
+        let centers: Vec<(f32, f32)> = cells
+            .iter()
+            .map(|&(x, y)| viewport.to_screen(x + size / 2.0, y + size / 2.0))
+            .collect();
+        
+        // Tail first so the head ends up on top; the circles at each center fill in the corners
+        for (index, &(x, y)) in centers.iter().enumerate().rev() {
+            let color = match index {
+                0 => head_color,
+                _ => body_color,
+            };
+            if let Some(&(next_x, next_y)) = centers.get(index + 1) {
+                // Neighbours more than a cell apart have just wrapped or gone through a portal
+                let (cell_dx, cell_dy) = (cells[index].0 - cells[index + 1].0, cells[index].1 - cells[index + 1].1);
+                if cell_dx.abs() + cell_dy.abs() <= 1.0 {
+                    draw_line(x, y, next_x, next_y, 2.0 * radius, body_color);
+                }
+            }
+            draw_circle(x, y, radius, color);
+        }
+    }
+    
+    fn draw_eyes(&self, viewport: &Viewport, x: f32, y: f32) {
+        // Eye offsets within the head cell, placed on the side facing the direction of travel
+        let eyes = match self.direction {
+            Direction::Up => [(0.3, 0.3), (0.7, 0.3)],
+            Direction::Down => [(0.3, 0.7), (0.7, 0.7)],
+            Direction::Left => [(0.3, 0.3), (0.3, 0.7)],
+            Direction::Right => [(0.7, 0.3), (0.7, 0.7)],
+        };
+        let size = self.footprint as f32;
+        let cell_size = size * viewport.cell_width.min(viewport.cell_height);
+        
+        for (eye_x, eye_y) in eyes {
+            let (screen_x, screen_y) = viewport.to_screen(x + eye_x * size, y + eye_y * size);
+            draw_circle(screen_x, screen_y, EYE_RADIUS * cell_size, WHITE);
+            draw_circle(screen_x, screen_y, PUPIL_RADIUS * cell_size, BLACK);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn snake() -> Snake {
+        Snake::new(Position::new(5, 10), 3, Direction::Right)
+    }
+    
+    fn head(snake: &Snake) -> Position {
+        *snake.body.front().unwrap()
+    }
+    
+    #[test]
+    fn rotating_four_times_comes_back_around() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.rotated(true).rotated(false), direction);
+            let mut turned = direction;
+            for _ in 0..4 {
+                turned = turned.rotated(true);
+                assert_ne!(turned.rotated(true), turned);
+            }
+            assert_eq!(turned, direction);
+        }
+        assert_eq!(Direction::Up.rotated(true), Direction::Right);
+        assert_eq!(Direction::Up.rotated(false), Direction::Left);
+    }
+    
+    #[test]
+    fn moving_right_increments_head_x() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let start = head(&snake);
+        
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(head(&snake), Position::new(start.x + 1, start.y));
+        assert_eq!(snake.body.len(), 3);
+    }
+    
+    #[test]
+    fn the_body_starts_out_behind_whichever_way_the_head_faces() {
+        let config = GameConfig::default();
+        let mut snake = Snake::new(Position::new(5, 10), 3, Direction::Up);
+        assert_eq!(
+            snake.body,
+            VecDeque::from([Position::new(5, 10), Position::new(5, 11), Position::new(5, 12)])
+        );
+        assert_eq!(snake.direction(), Direction::Up);
+        
+        // Turning back down onto the neck is refused from the very first move
+        snake.change_direction(Direction::Down);
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(head(&snake), Position::new(5, 9));
+        assert!(snake.alive);
+    }
+    
+    #[test]
+    fn growing_keeps_tail() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let tail = *snake.body.back().unwrap();
+        
+        snake.grow();
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(snake.body.len(), 4);
+        assert_eq!(*snake.body.back().unwrap(), tail);
+    }
+    
+    #[test]
+    fn reversing_direction_is_rejected() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let start = head(&snake);
+        
+        snake.change_direction(Direction::Left);
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(snake.direction, Direction::Right);
+        assert_eq!(head(&snake), Position::new(start.x + 1, start.y));
+    }
+    
+    #[test]
+    fn queued_turns_chain_off_each_other() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let start = head(&snake);
+        
+        snake.change_direction(Direction::Up);
+        snake.change_direction(Direction::Left);
+        snake.update(&config, WallMode::Solid, &[]);
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(snake.direction, Direction::Left);
+        assert_eq!(head(&snake), Position::new(start.x - 1, start.y - 1));
+    }
+    
+    #[test]
+    fn heading_follows_the_last_buffered_turn() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        assert_eq!(snake.heading(), Direction::Right);
+        
+        snake.change_direction(Direction::Up);
+        snake.change_direction(Direction::Left);
+        assert_eq!(snake.heading(), Direction::Left);
+        assert_eq!(snake.direction(), Direction::Right);
+        
+        snake.update(&config, WallMode::Solid, &[]);
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(snake.heading(), snake.direction());
+    }
+    
+    #[test]
+    fn queued_reversal_of_a_buffered_turn_is_rejected() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let start = head(&snake);
+        
+        snake.change_direction(Direction::Up);
+        snake.change_direction(Direction::Down);
+        snake.update(&config, WallMode::Solid, &[]);
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(snake.direction, Direction::Up);
+        assert_eq!(head(&snake), Position::new(start.x, start.y - 2));
+    }
+    
+    #[test]
+    fn two_quick_turns_never_reverse_into_the_neck() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let start = head(&snake);
+        
+        // Right, then Up and Left inside one tick: the left turn waits for the move up
+        snake.change_direction(Direction::Up);
+        snake.change_direction(Direction::Left);
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(head(&snake), Position::new(start.x, start.y - 1));
+        assert!(!snake.check_self_collision(&config));
+        
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(head(&snake), Position::new(start.x - 1, start.y - 1));
+        assert!(!snake.check_self_collision(&config));
+    }
+    
+    #[test]
+    fn looping_back_reports_self_collision() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        for _ in 0..2 {
+            snake.grow();
+            snake.update(&config, WallMode::Solid, &[]);
+        }
+        assert!(!snake.check_self_collision(&config));
+        
+        for direction in [Direction::Down, Direction::Left, Direction::Up] {
+            snake.change_direction(direction);
+            snake.update(&config, WallMode::Solid, &[]);
+        }
+        
+        assert!(snake.check_self_collision(&config));
+    }
+    
+    #[test]
+    fn leaving_the_board_reports_wall_collision() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        snake.change_direction(Direction::Up);
+        
+        for _ in 0..10 {
+            snake.update(&config, WallMode::Solid, &[]);
+            assert!(!snake.check_wall_collision(&config, WallMode::Solid));
+        }
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert!(snake.check_wall_collision(&config, WallMode::Solid));
+    }
+    
+    #[test]
+    fn entering_a_portal_moves_the_head_to_its_pair() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let portals = [(Position::new(20, 20), Position::new(6, 10))];
+        
+        snake.update(&config, WallMode::Solid, &portals);
+        
+        assert_eq!(head(&snake), Position::new(20, 20));
+    }
+    
+    #[test]
+    fn portal_exiting_onto_the_neck_is_ignored() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let portals = [(Position::new(5, 10), Position::new(6, 10))];
+        
+        snake.update(&config, WallMode::Solid, &portals);
+        
+        assert_eq!(head(&snake), Position::new(6, 10));
+        assert!(!snake.check_self_collision(&config));
+    }
+    
+    #[test]
+    fn shrinking_below_one_segment_fails() {
+        let mut snake = snake();
+        
+        assert!(snake.shrink(2));
+        assert_eq!(snake.body.len(), 1);
+        assert!(!snake.shrink(1));
+        assert_eq!(snake.body.len(), 1);
+    }
+    
+    #[test]
+    fn next_head_follows_the_buffered_turn() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        snake.change_direction(Direction::Up);
+        
+        let predicted = snake.next_head(&config, WallMode::Solid, &[]);
+        snake.update(&config, WallMode::Solid, &[]);
+        
+        assert_eq!(predicted, head(&snake));
+    }
+    
+    #[test]
+    fn a_lone_head_moves_and_grows() {
+        let config = GameConfig::default();
+        let mut snake = Snake::new(Position::new(5, 10), 1, Direction::Right);
+        
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(snake.body, [Position::new(6, 10)]);
+        assert!(!snake.check_self_collision(&config));
+        assert!(!snake.check_wall_collision(&config, WallMode::Solid));
+        
+        snake.grow();
+        snake.update(&config, WallMode::Solid, &[]);
+        assert_eq!(snake.body.len(), 2);
+        assert!(!snake.shrink(2));
+    }
+    
+    #[test]
+    fn occupancy_stays_in_step_with_the_body() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        let moves = [Direction::Up, Direction::Up, Direction::Left, Direction::Down, Direction::Left];
+        
+        for (step, direction) in moves.into_iter().enumerate() {
+            if step % 2 == 0 {
+                snake.grow();
+            }
+            snake.change_direction(direction);
+            snake.update(&config, WallMode::Solid, &[]);
+        }
+        assert!(snake.shrink(2));
+        
+        let mut counts = HashMap::new();
+        for &segment in &snake.body {
+            *counts.entry(segment).or_insert(0) += 1;
+        }
+        assert_eq!(snake.occupancy, counts);
+    }
+    
+    #[test]
+    fn an_enlarged_snake_covers_more_cells_until_it_shrinks_back() {
+        let config = GameConfig::default();
+        let mut snake = snake();
+        snake.enlarge(2, 3);
+        
+        assert!(snake.head_covers(Position::new(6, 11), &config));
+        assert!(snake.covers(Position::new(3, 11), &config));
+        // A plain corner doesn't run the wide head into its own neck
+        snake.change_direction(Direction::Up);
+        snake.update(&config, WallMode::Solid, &[]);
+        snake.change_direction(Direction::Left);
+        snake.update(&config, WallMode::Solid, &[]);
+        assert!(!snake.check_self_collision(&config));
+        
+        snake.update(&config, WallMode::Solid, &[]);
+        assert!(!snake.head_covers(Position::new(4, 9), &config));
+        assert!(!snake.covers(Position::new(3, 11), &config));
+    }
+    
+    #[test]
+    fn an_enlarged_head_hits_the_wall_a_cell_early() {
+        let config = GameConfig::default();
+        let mut snake = Snake::new(Position::new(config.cell_number_x - 1, 4), 3, Direction::Right);
+        assert!(!snake.check_wall_collision(&config, WallMode::Solid));
+        
+        snake.enlarge(2, 10);
+        assert!(snake.check_wall_collision(&config, WallMode::Solid));
+    }
+    
+    #[test]
+    fn wrapping_the_head_brings_it_back_on_the_board() {
+        let config = GameConfig::default();
+        let mut snake = Snake::new(Position::new(config.cell_number_x - 1, 4), 3, Direction::Right);
+        
+        snake.update(&config, WallMode::OneFreeWrap, &[]);
+        assert!(snake.check_wall_collision(&config, WallMode::OneFreeWrap));
+        
+        snake.wrap_head(&config);
+        assert_eq!(head(&snake), Position::new(0, 4));
+        assert!(!snake.check_wall_collision(&config, WallMode::OneFreeWrap));
+    }
+}
@@ -0,0 +1,7 @@
+use macroquad::prelude::*;
+
+// Images drawn in place of plain cells; any that fail to load are drawn as cells instead
+#[derive(Clone, Default)]
+pub struct Sprites {
+    pub apple: Option<Texture2D>,
+}
@@ -0,0 +1,30 @@
+use macroquad::prelude::*;
+use std::cell::RefCell;
+
+thread_local! {
+    // What all text is drawn with; `None` is macroquad's built-in font
+    static FONT: RefCell<Option<Font>> = const { RefCell::new(None) };
+}
+
+// Switches all text to `font`, or back to the built-in one
+pub fn set_font(font: Option<Font>) {
+    FONT.with(|slot| *slot.borrow_mut() = font);
+}
+
+// Every piece of text goes through here and `measure`, so they always agree on the font
+pub fn draw(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+    FONT.with(|font| {
+        let font = font.borrow();
+        let params = TextParams {
+            font: font.as_ref(),
+            font_size: font_size as u16,
+            color,
+            ..Default::default()
+        };
+        draw_text_ex(text, x, y, params);
+    });
+}
+
+pub fn measure(text: &str, font_size: f32) -> TextDimensions {
+    FONT.with(|font| measure_text(text, font.borrow().as_ref(), font_size as u16, 1.0))
+}
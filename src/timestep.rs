@@ -0,0 +1,88 @@
+const MAX_STEPS_PER_FRAME: u32 = 4; // Catch-up ticks allowed after a single slow frame
+const ROUNDING_SLACK: f64 = 1e-9; // Keeps repeated subtraction from losing a tick to float error
+
+// Banks frame time and pays it out in whole ticks, so the snake moves at the same rate whatever
+// the frame rate. Only frames that are fed in count, so time spent paused simply never arrives.
+#[derive(Default)]
+pub struct FixedTimestep {
+    accumulated: f64,
+}
+
+impl FixedTimestep {
+    pub fn accumulate(&mut self, frame_time: f64, interval: f64) {
+        // After a long stall, drop what can't be caught up on rather than teleporting the snake
+        self.accumulated = (self.accumulated + frame_time).min(interval * MAX_STEPS_PER_FRAME as f64);
+    }
+    
+    // Take one tick's worth of time if that much has built up
+    pub fn consume(&mut self, interval: f64) -> bool {
+        if self.accumulated + ROUNDING_SLACK < interval {
+            return false;
+        }
+        self.accumulated = (self.accumulated - interval).max(0.0);
+        true
+    }
+    
+    // Fraction of the way to the next tick
+    pub fn progress(&self, interval: f64) -> f64 {
+        (self.accumulated / interval).clamp(0.0, 1.0)
+    }
+    
+    // Keep the same share of the way to the next tick when the interval changes, so switching to a
+    // shorter one doesn't pay out the difference as a burst of ticks
+    pub fn rescale(&mut self, from: f64, to: f64) {
+        self.accumulated *= to / from;
+    }
+    
+    pub fn reset(&mut self) {
+        self.accumulated = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const INTERVAL: f64 = 0.15;
+    
+    fn steps(timestep: &mut FixedTimestep) -> u32 {
+        let mut steps = 0;
+        while timestep.consume(INTERVAL) {
+            steps += 1;
+        }
+        steps
+    }
+    
+    #[test]
+    fn regular_frames_tick_at_the_interval() {
+        let mut timestep = FixedTimestep::default();
+        let mut total = 0;
+        for _ in 0..60 {
+            timestep.accumulate(1.0 / 60.0, INTERVAL);
+            total += steps(&mut timestep);
+        }
+        
+        // One second of frames at 0.15s per tick
+        assert_eq!(total, 6);
+    }
+    
+    #[test]
+    fn rescaling_keeps_progress_instead_of_banking_ticks() {
+        let mut timestep = FixedTimestep::default();
+        timestep.accumulate(0.9 * INTERVAL, INTERVAL);
+        
+        timestep.rescale(INTERVAL, INTERVAL / 2.0);
+        assert!((timestep.progress(INTERVAL / 2.0) - 0.9).abs() < 1e-9);
+        assert!(!timestep.consume(INTERVAL / 2.0));
+    }
+    
+    #[test]
+    fn a_huge_frame_gap_is_capped() {
+        let mut timestep = FixedTimestep::default();
+        timestep.accumulate(30.0, INTERVAL);
+        
+        assert_eq!(steps(&mut timestep), MAX_STEPS_PER_FRAME);
+        timestep.accumulate(1.0 / 60.0, INTERVAL);
+        assert_eq!(steps(&mut timestep), 0);
+    }
+}
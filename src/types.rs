@@ -0,0 +1,309 @@
+use macroquad::prelude::*;
+
+use crate::text;
+
+const WINDOW_WIDTH: f32 = 800.0;
+const WINDOW_HEIGHT: f32 = 600.0;
+const CELL_WIDTH: f32 = 20.0;
+const CELL_HEIGHT: f32 = 20.0;
+// Strip across the top of the window for the score readouts, kept clear of the board
+const HUD_HEIGHT: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct GameConfig {
+    pub window_width: f32,
+    pub window_height: f32,
+    // Part of `window_height` reserved above the board
+    pub hud_height: f32,
+    pub cell_number_x: i32,
+    pub cell_number_y: i32,
+    // Size of a cell at the configured window size. Near the asked for size, but stretched so the
+    // cells fill the board exactly; the viewport scales them again as the window is resized
+    pub cell_width: f32,
+    pub cell_height: f32,
+}
+
+impl GameConfig {
+    pub fn new(window_width: f32, window_height: f32, hud_height: f32, cell_width: f32, cell_height: f32) -> Self {
+        let config = Self {
+            window_width,
+            window_height,
+            hud_height,
+            cell_number_x: 0,
+            cell_number_y: 0,
+            cell_width,
+            cell_height,
+        };
+        config.with_cells(
+            (window_width / cell_width) as i32,
+            ((window_height - hud_height) / cell_height) as i32,
+        )
+    }
+    
+    pub fn with_cell_size(cell_size: f32) -> Self {
+        Self::new(WINDOW_WIDTH, WINDOW_HEIGHT, HUD_HEIGHT, cell_size, cell_size)
+    }
+    
+    // The same window split into a different number of cells
+    pub fn with_cells(self, cell_number_x: i32, cell_number_y: i32) -> Self {
+        Self {
+            cell_number_x,
+            cell_number_y,
+            cell_width: self.window_width / cell_number_x as f32,
+            cell_height: (self.window_height - self.hud_height) / cell_number_y as f32,
+            ..self
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::new(WINDOW_WIDTH, WINDOW_HEIGHT, HUD_HEIGHT, CELL_WIDTH, CELL_HEIGHT)
+    }
+}
+
+// The playable part of the board; `right` and `bottom` are exclusive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Bounds {
+    pub fn full(config: &GameConfig) -> Self {
+        Self {
+            left: 0,
+            top: 0,
+            right: config.cell_number_x,
+            bottom: config.cell_number_y,
+        }
+    }
+    
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+    
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+    
+    pub fn contains(&self, position: Position) -> bool {
+        (self.left..self.right).contains(&position.x) && (self.top..self.bottom).contains(&position.y)
+    }
+    
+    // Pull every edge in by one cell
+    pub fn shrink(&mut self) {
+        self.left += 1;
+        self.top += 1;
+        self.right -= 1;
+        self.bottom -= 1;
+    }
+}
+
+// Cells drawn any smaller than this make the board scroll instead of shrinking further
+const MIN_CELL_PIXELS: f32 = 12.0;
+
+// Screen-space layout of the board, recomputed every frame so the grid stretches with the window
+#[derive(Clone)]
+pub struct Viewport {
+    pub width: f32,
+    pub height: f32,
+    pub cell_width: f32,
+    pub cell_height: f32,
+    pub text_scale: f32,
+    // Height of the HUD strip on screen; the board starts below it
+    pub hud_height: f32,
+    // Added to every board coordinate: the HUD strip, plus e.g. the shake after a crash
+    pub offset: Vec2,
+}
+
+impl Viewport {
+    pub fn current(config: &GameConfig) -> Self {
+        let width = screen_width();
+        let height = screen_height();
+        // The strip keeps its share of the window as it's resized
+        let hud_height = config.hud_height * height / config.window_height;
+        Self {
+            width,
+            height,
+            cell_width: config.cell_width * width / config.window_width,
+            cell_height: config.cell_height * (height - hud_height) / (config.window_height - config.hud_height),
+            text_scale: (width / config.window_width).min(height / config.window_height),
+            hud_height,
+            offset: vec2(0.0, hud_height),
+        }
+    }
+    
+    // Whether the board is too big to show whole at a readable size
+    pub fn needs_scrolling(&self) -> bool {
+        self.cell_width.min(self.cell_height) < MIN_CELL_PIXELS
+    }
+    
+    // Zoomed in to readable cells and scrolled to keep `focus`, in cells, central without showing past
+    // the edges of the board; the whole board, as before, when it fits
+    pub fn following(self, config: &GameConfig, focus: Vec2) -> Self {
+        if !self.needs_scrolling() {
+            return self;
+        }
+        
+        let cell_width = self.cell_width.max(MIN_CELL_PIXELS);
+        let cell_height = self.cell_height.max(MIN_CELL_PIXELS);
+        let scroll = |focus: f32, cell: f32, cells: i32, visible: f32| {
+            (focus * cell - visible / 2.0).clamp(0.0, (cells as f32 * cell - visible).max(0.0))
+        };
+        let x = scroll(focus.x, cell_width, config.cell_number_x, self.width);
+        let y = scroll(focus.y, cell_height, config.cell_number_y, self.height - self.hud_height);
+        Self {
+            cell_width,
+            cell_height,
+            offset: self.offset - vec2(x, y),
+            ..self
+        }
+    }
+    
+    pub fn shifted(self, offset: Vec2) -> Self {
+        Self {
+            offset: self.offset + offset,
+            ..self
+        }
+    }
+    
+    pub fn draw_cell(&self, position: Position, color: Color) {
+        self.draw_cell_at(position.x as f32, position.y as f32, color);
+    }
+    
+    // Draw a cell at fractional grid coordinates
+    pub fn draw_cell_at(&self, x: f32, y: f32, color: Color) {
+        let (x, y) = self.to_screen(x, y);
+        draw_rectangle(x, y, self.cell_width, self.cell_height, color);
+    }
+    
+    // A `size` by `size` block of cells at fractional grid coordinates, pulled in by `inset` cells on
+    // every side
+    pub fn draw_inset_cell(&self, x: f32, y: f32, size: f32, inset: f32, color: Color) {
+        let (screen_x, screen_y) = self.to_screen(x + inset, y + inset);
+        let side = (size - 2.0 * inset).max(0.0);
+        draw_rectangle(screen_x, screen_y, self.cell_width * side, self.cell_height * side, color);
+    }
+    
+    // `texture` stretched over the same square `draw_inset_cell` would fill
+    pub fn draw_inset_texture(&self, x: f32, y: f32, size: f32, inset: f32, texture: &Texture2D) {
+        let (screen_x, screen_y) = self.to_screen(x + inset, y + inset);
+        let side = (size - 2.0 * inset).max(0.0);
+        let params = DrawTextureParams {
+            dest_size: Some(vec2(self.cell_width * side, self.cell_height * side)),
+            ..Default::default()
+        };
+        draw_texture_ex(texture, screen_x, screen_y, WHITE, params);
+    }
+    
+    pub fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.cell_width + self.offset.x, y * self.cell_height + self.offset.y)
+    }
+    
+    pub fn draw_centered_text(&self, text: &str, y: f32, font_size: f32, color: Color) {
+        let font_size = font_size * self.text_scale;
+        let dimensions = text::measure(text, font_size);
+        text::draw(text, (self.width - dimensions.width) / 2.0, y, font_size, color);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WallMode {
+    Solid,
+    Wrap,
+    // Solid, except the first wall hit of a run wraps around instead
+    OneFreeWrap,
+}
+
+impl WallMode {
+    pub const ALL: [WallMode; 3] = [WallMode::Solid, WallMode::Wrap, WallMode::OneFreeWrap];
+    
+    // Wall hits per run that wrap instead of ending the run
+    pub fn free_wraps(self) -> u32 {
+        match self {
+            WallMode::OneFreeWrap => 1,
+            WallMode::Solid | WallMode::Wrap => 0,
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            WallMode::Solid => "Solid",
+            WallMode::Wrap => "Wrap",
+            WallMode::OneFreeWrap => "One free wrap",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn shrinking_walls_off_the_outer_ring() {
+        let mut bounds = Bounds::full(&GameConfig::new(100.0, 100.0, 0.0, 10.0, 10.0));
+        assert!(bounds.contains(Position::new(0, 0)));
+        
+        bounds.shrink();
+        assert_eq!((bounds.width(), bounds.height()), (8, 8));
+        assert!(!bounds.contains(Position::new(0, 5)));
+        assert!(!bounds.contains(Position::new(9, 5)));
+        assert!(bounds.contains(Position::new(1, 1)));
+        assert!(bounds.contains(Position::new(8, 8)));
+    }
+    
+    #[test]
+    fn a_board_too_big_for_the_window_scrolls_with_the_focus() {
+        let config = GameConfig::default().with_cells(200, 100);
+        let fitted = Viewport {
+            width: 800.0,
+            height: 640.0,
+            cell_width: 4.0,
+            cell_height: 6.0,
+            text_scale: 1.0,
+            hud_height: 40.0,
+            offset: vec2(0.0, 40.0),
+        };
+        assert!(fitted.needs_scrolling());
+        
+        let view = fitted.clone().following(&config, vec2(100.0, 50.0));
+        assert_eq!((view.cell_width, view.cell_height), (MIN_CELL_PIXELS, MIN_CELL_PIXELS));
+        assert_eq!(view.to_screen(100.0, 50.0), (400.0, 340.0));
+        // Near a corner it stops at the edge of the board
+        let view = fitted.following(&config, vec2(1.0, 1.0));
+        assert_eq!(view.to_screen(0.0, 0.0), (0.0, 40.0));
+    }
+    
+    #[test]
+    fn the_hud_strip_takes_rows_off_the_board() {
+        let config = GameConfig::new(100.0, 100.0, 20.0, 10.0, 10.0);
+        assert_eq!((config.cell_number_x, config.cell_number_y), (10, 8));
+    }
+    
+    #[test]
+    fn cells_can_be_wider_than_they_are_tall() {
+        let config = GameConfig::new(100.0, 100.0, 20.0, 20.0, 10.0);
+        assert_eq!((config.cell_number_x, config.cell_number_y), (5, 8));
+        assert_eq!((config.cell_width, config.cell_height), (20.0, 10.0));
+        
+        // Sizes that don't divide the board are stretched to fill it
+        let config = GameConfig::new(100.0, 100.0, 20.0, 30.0, 30.0);
+        assert_eq!((config.cell_number_x, config.cell_number_y), (3, 2));
+        assert_eq!(config.cell_height, 40.0);
+    }
+}